@@ -0,0 +1,48 @@
+use std::fs;
+use std::path::PathBuf;
+
+use log::debug;
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::APP_NAME;
+
+pub fn load<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let path = path(name)?;
+    debug!("Trying to load cached {} from {}", name, path.to_str()?);
+    let cached = fs::read_to_string(&path).ok()?;
+    match toml::from_str(&cached) {
+        Ok(t) => Some(t),
+        Err(err) => {
+            warn!("Ignoring malformed cache file {}: {}", path.to_str()?, err);
+            None
+        }
+    }
+}
+
+pub fn save<T: Serialize>(name: &str, t: &T) -> crate::Result<()> {
+    let path = path(name).ok_or("Couldn't get cache path")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, toml::to_string(t)?)?;
+    debug!("Cached {} under {:?}", name, path);
+    Ok(())
+}
+
+#[cfg(not(any(test, feature = "test")))]
+fn path(name: &str) -> Option<PathBuf> {
+    let project = directories::ProjectDirs::from("rs", APP_NAME, APP_NAME)?;
+    Some(project.cache_dir().join(format!("{}.toml", name)))
+}
+
+// don't touch the user's cache when testing
+#[cfg(any(test, feature = "test"))]
+fn path(name: &str) -> Option<PathBuf> {
+    Some(
+        std::env::temp_dir()
+            .join(APP_NAME)
+            .join(format!("{}.toml", name)),
+    )
+}
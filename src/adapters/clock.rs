@@ -0,0 +1,51 @@
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use log::debug;
+
+use crate::model::event::Event;
+
+/// How often the wall clock is compared to the monotonic clock
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// How far the clocks may drift apart before it counts as a jump
+const TOLERANCE: Duration = Duration::from_secs(30);
+
+/// Sends an event whenever the wall clock jumps in a background thread
+///
+/// The monotonic clock stands still while the machine is suspended, so the wall clock
+/// jumps ahead of it on waking up. Setting the time makes it jump, too.
+pub fn watch(events: Sender<Event>) {
+    thread::spawn(move || {
+        let mut instant = Instant::now();
+        let mut wall = SystemTime::now();
+        loop {
+            thread::sleep(CHECK_INTERVAL);
+            let (last_instant, last_wall) = (instant, wall);
+            instant = Instant::now();
+            wall = SystemTime::now();
+            if !jumped(instant - last_instant, wall.duration_since(last_wall).ok()) {
+                continue;
+            }
+            debug!("Wall clock jumped");
+            if events.send(Event::ClockJump).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Whether the wall clock didn't move like the monotonic clock, which includes going backwards
+fn jumped(elapsed: Duration, wall_elapsed: Option<Duration>) -> bool {
+    wall_elapsed.is_none_or(|wall_elapsed| wall_elapsed.abs_diff(elapsed) > TOLERANCE)
+}
+
+#[test]
+fn jumps() {
+    let elapsed = Duration::from_secs(10);
+    assert!(!jumped(elapsed, Some(Duration::from_secs(11))));
+    // woke up after an hour
+    assert!(jumped(elapsed, Some(Duration::from_secs(3610))));
+    // set back
+    assert!(jumped(elapsed, None));
+}
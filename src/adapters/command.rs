@@ -0,0 +1,69 @@
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::debug;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Runs a shell command and returns its stdout, killing it after the timeout
+pub fn run(command: &str, timeout: Duration) -> crate::Result<String> {
+    run_with_env(command, &[], timeout)
+}
+
+/// Runs a shell command with additional environment variables
+pub fn run_with_env(
+    command: &str,
+    env: &[(&str, String)],
+    timeout: Duration,
+) -> crate::Result<String> {
+    debug!("Running {} ...", command);
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    // read concurrently so the command doesn't block on a full pipe
+    let mut stdout = child.stdout.take().ok_or("Couldn't capture stdout")?;
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if start.elapsed() > timeout {
+            child.kill()?;
+            child.wait()?;
+            return Err(format!("'{}' timed out after {}s", command, timeout.as_secs()).into());
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let output = reader.join().map_err(|_| "Couldn't read stdout")??;
+    if !status.success() {
+        return Err(format!("'{}' failed with {}", command, status).into());
+    }
+    Ok(output)
+}
+
+#[test]
+fn run_command() {
+    let timeout = Duration::from_secs(5);
+    assert_eq!(run("echo 21.5", timeout).unwrap(), "21.5\n");
+    assert!(run("exit 1", timeout).is_err());
+    assert!(run("sleep 5", Duration::from_millis(100)).is_err());
+
+    let env = [("WEDDER_LAT", "51.5".to_string())];
+    assert_eq!(
+        run_with_env("echo $WEDDER_LAT", &env, timeout).unwrap(),
+        "51.5\n"
+    );
+}
@@ -1,16 +1,22 @@
 use std::process;
 
+use chrono::NaiveDate;
 use log::debug;
 use structopt::StructOpt;
 
 use crate::adapters::config::file;
 use crate::logger;
+use crate::model::config::AqiUnit;
 use crate::model::config::DistanceUnit;
 use crate::model::config::Format;
+use crate::model::config::HistoryLength;
 use crate::model::config::Interval;
+use crate::model::config::MinDistance;
 use crate::model::config::PrecipitationUnit;
 use crate::model::config::TemperatureUnit;
+use crate::model::config::TimeZone;
 use crate::model::config::WindSpeedUnit;
+use crate::model::language::Language;
 use crate::model::location::LocationProvider;
 use crate::model::weather::WeatherProvider;
 
@@ -32,22 +38,62 @@ pub struct CliArgs {
     /// Available tags:
     /// <city>
     /// <icon>
+    /// <condition>
+    /// <description>
     /// <temperature>
     /// <temperature_feels_like>
     /// <temperature_max>
     /// <temperature_min>
     /// <dew_point>
+    /// <indoor_temperature>
+    /// <indoor_humidity>
+    /// <heat_index>
+    /// <wind_chill>
+    /// <humidex>
+    /// <absolute_humidity>
     /// <precipitation>
     /// <precipitation_chance>
     /// <clouds>
     /// <humidity>
     /// <visibility>
     /// <wind_speed>
+    /// <wind_gust>
+    /// <wind_direction>
+    /// <wind_cardinal>
+    /// <wind_arrow>
     /// <pressure>
     /// <uv_index>
     /// <air_quality_index>
+    /// <air_quality>
+    /// <pm2_5>
+    /// <pm10>
+    /// <o3>
+    /// <no2>
+    /// <so2>
+    /// <co>
+    /// <nh3>
+    /// <temperature_trend>
+    /// <pressure_trend>
+    /// <temperature_delta_1h>
+    /// <alder_pollen>
+    /// <birch_pollen>
+    /// <grass_pollen>
+    /// <mugwort_pollen>
+    /// <olive_pollen>
+    /// <ragweed_pollen>
+    /// <pollen_level>
     /// <sunrise>
     /// <sunset>
+    /// <day_length>
+    /// <sunset_in>
+    /// <civil_dawn>
+    /// <civil_dusk>
+    /// <golden_hour>
+    /// <moon_phase>
+    /// <moon_icon>
+    /// <moon_illumination>
+    /// <last_update>
+    /// <age>
     ///
     /// Default: '<icon> <temperature>°C'
     #[structopt(short = "f", long)]
@@ -73,7 +119,8 @@ pub struct CliArgs {
     /// Available units:
     /// Ms,
     /// Kmh,
-    /// Mph
+    /// Mph,
+    /// Beaufort
     ///
     /// Default: Kmh
     #[structopt(short = "s", long)]
@@ -97,16 +144,46 @@ pub struct CliArgs {
     /// Default: Millimeter
     #[structopt(short = "P", long)]
     pub precipitation_unit: Option<PrecipitationUnit>,
+    /// The scale of the air quality index
+    ///
+    /// Available units:
+    /// Owm,
+    /// UsEpa,
+    /// Caqi
+    ///
+    /// Default: Owm
+    #[structopt(short = "A", long)]
+    pub aqi_unit: Option<AqiUnit>,
+    /// The time zone to display times like sunrise and sunset in
+    ///
+    /// Available time zones:
+    /// Machine,
+    /// Location
+    ///
+    /// Default: Machine
+    #[structopt(short = "z", long)]
+    pub time_zone: Option<TimeZone>,
+    /// The language of texts like weather conditions and of the decimal separator
+    ///
+    /// Available languages:
+    /// en,
+    /// de,
+    /// fr
+    ///
+    /// Default: en
+    #[structopt(short = "L", long)]
+    pub language: Option<Language>,
 
     /// The provider to use for pulling weather updates
     ///
     /// Available providers:
-    /// OpenWeatherMap
+    /// OpenWeatherMap, WeatherUnderground, Netatmo, Ecowitt, Command
     ///
     /// Default: OpenWeatherMap
     #[structopt(short = "w", long)]
     pub weather_provider: Option<WeatherProvider>,
-    /// The API key for the corresponding weather provider
+    /// The API key for OpenWeatherMap, other weather providers are set up in their section of the
+    /// config file
     #[structopt(short = "k", long)]
     pub weather_api_key: Option<String>,
 
@@ -114,17 +191,61 @@ pub struct CliArgs {
     ///
     /// Available providers:
     /// IpApi,
-    /// Manual
+    /// Manual,
+    /// Network,
+    /// Command
     ///
     /// Default: IpApi
     #[structopt(short = "l", long)]
     pub location_provider: Option<LocationProvider>,
+    /// The interval in seconds how often the location is updated
+    ///
+    /// If a negative interval is specified, the location is only determined once
+    ///
+    /// Default: 300
+    #[structopt(long)]
+    pub location_interval: Option<Interval>,
+    /// The distance in meters the location has to change by to be considered a new location
+    ///
+    /// Default: 1000
+    #[structopt(long)]
+    pub min_distance: Option<MinDistance>,
     /// Latitude of the location to display the weather status for
     #[structopt(long)]
     pub lat: Option<f32>,
     /// Longitude of the location to display the weather status for
     #[structopt(long)]
     pub lon: Option<f32>,
+
+    /// How long readings are kept in seconds for the trend tags
+    ///
+    /// Default: 10800
+    #[structopt(long)]
+    pub history_length: Option<HistoryLength>,
+
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt, Clone, Eq, PartialEq)]
+pub enum Command {
+    /// Prints the minimum, maximum and average of the weather recorded by the recorder
+    History {
+        /// The first day to summarize, e.g. 2024-12-24
+        ///
+        /// Default: today
+        #[structopt(long)]
+        from: Option<NaiveDate>,
+        /// The last day to summarize
+        ///
+        /// Default: the first day
+        #[structopt(long)]
+        to: Option<NaiveDate>,
+    },
+    /// Makes the running wedder poll the weather right away, like sending it SIGUSR1
+    Refresh,
+    /// Makes the running wedder display the weather in the next of the formats
+    NextFormat,
 }
 
 impl CliArgs {
@@ -45,27 +45,27 @@ pub fn default_config_path() -> Option<PathBuf> {
 
 fn load_config(path: &Path) -> Option<Config> {
     debug!("Trying to open config file under {}", path.to_str()?);
-    let cfg_str = match fs::read_to_string(path) {
-        Ok(cfg_str) => Some(cfg_str),
-        Err(err) => match err.kind() {
-            io::ErrorKind::NotFound => None,
-            _ => malformed_config(err),
-        },
-    };
-
-    if cfg_str.is_none() {
-        warn!(
-            "No config file found under {}, using defaults",
-            path.to_str()?
-        );
-        return Some(Config::default());
-    }
-    match toml::from_str(&cfg_str?) {
+    match read(path) {
         Ok(config) => Some(config),
         Err(err) => malformed_config(err),
     }
 }
 
+/// Reads the config file without exiting on errors, so a running wedder can keep its config
+pub fn read(path: &Path) -> crate::Result<Config> {
+    match fs::read_to_string(path) {
+        Ok(cfg_str) => Ok(toml::from_str(&cfg_str)?),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            warn!(
+                "No config file found under {}, using defaults",
+                path.display()
+            );
+            Ok(Config::default())
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
 fn malformed_config<E: Display>(err: E) -> ! {
     error!("Error parsing config file: {}", err.to_string());
     println!("Malformed config file");
@@ -78,3 +78,11 @@ fn path_not_found() {
     assert!(config.is_some());
     assert_eq!(config.unwrap(), Config::default());
 }
+
+#[test]
+fn malformed() {
+    let path = std::env::temp_dir().join(format!("wedder-malformed-{}.toml", process::id()));
+    fs::write(&path, "interval = 'often'").unwrap();
+    assert!(read(&path).is_err());
+    fs::remove_file(path).unwrap();
+}
@@ -1,2 +1,3 @@
 pub mod cli_args;
 pub mod file;
+pub mod watcher;
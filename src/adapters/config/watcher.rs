@@ -0,0 +1,67 @@
+use std::ffi::OsString;
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+use inotify::{Inotify, WatchMask};
+use log::{debug, error};
+
+use crate::model::event::Event;
+
+/// Sends a reload event whenever the config file is written
+///
+/// The directory is watched instead of the file itself, since many editors
+/// replace the file on saving, which would end a watch on the file.
+pub fn watch(path: &Path, events: Sender<Event>) -> crate::Result<()> {
+    let file_name: OsString = path
+        .file_name()
+        .ok_or("Config path has no file name")?
+        .to_owned();
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut inotify = Inotify::init()?;
+    let mask = WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO | WatchMask::CREATE;
+    inotify.watches().add(dir, mask)?;
+    debug!("Watching {} for changes", path.display());
+
+    thread::spawn(move || {
+        let mut buffer = [0; 4096];
+        loop {
+            let changed = match inotify.read_events_blocking(&mut buffer) {
+                Ok(mut changes) => changes.any(|change| change.name == Some(file_name.as_os_str())),
+                Err(err) => {
+                    error!("Error watching config file: {}", err);
+                    return;
+                }
+            };
+            if changed && events.send(Event::Reload).is_err() {
+                return;
+            }
+        }
+    });
+    Ok(())
+}
+
+#[test]
+fn watch_config_file() {
+    use std::fs;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let dir = std::env::temp_dir().join(format!("wedder-watch-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("wedder.toml");
+    let (sender, receiver) = mpsc::channel();
+    watch(&path, sender).unwrap();
+
+    fs::write(dir.join("other.toml"), "").unwrap();
+    assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+    fs::write(&path, "interval = 60").unwrap();
+    let event = receiver.recv_timeout(Duration::from_secs(5));
+    assert_eq!(event, Ok(Event::Reload));
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,56 @@
+use std::sync::mpsc::Sender;
+use std::thread;
+
+use log::{debug, warn};
+use zbus::blocking::{Connection, MessageIterator, Proxy};
+use zbus::message::Type;
+use zbus::MatchRule;
+
+use crate::model::event::Event;
+
+pub const NETWORK_MANAGER: &str = "org.freedesktop.NetworkManager";
+pub const NETWORK_MANAGER_PATH: &str = "/org/freedesktop/NetworkManager";
+/// NM_STATE_CONNECTED_GLOBAL, the internet is reachable
+const CONNECTED: u32 = 70;
+
+/// Sends an event whenever the internet becomes reachable in a background thread,
+/// as announced by NetworkManager
+pub fn watch(events: Sender<Event>) -> crate::Result<()> {
+    let connection = Connection::system()?;
+    let network_manager = Proxy::new(
+        &connection,
+        NETWORK_MANAGER,
+        NETWORK_MANAGER_PATH,
+        NETWORK_MANAGER,
+    )?;
+    let mut state: u32 = network_manager.get_property("State")?;
+    debug!("Network state {}", state);
+    let rule = MatchRule::builder()
+        .msg_type(Type::Signal)
+        .sender(NETWORK_MANAGER)?
+        .path(NETWORK_MANAGER_PATH)?
+        .interface(NETWORK_MANAGER)?
+        .member("StateChanged")?
+        .build();
+    let messages = MessageIterator::for_match_rule(rule, &connection, None)?;
+
+    thread::spawn(move || {
+        for message in messages {
+            let result = message.and_then(|message| message.body().deserialize::<u32>());
+            let new_state = match result {
+                Ok(new_state) => new_state,
+                Err(err) => {
+                    warn!("Error handling NetworkManager signal: {}", err);
+                    continue;
+                }
+            };
+            debug!("Network state changed from {} to {}", state, new_state);
+            let online = state != CONNECTED && new_state == CONNECTED;
+            state = new_state;
+            if online && events.send(Event::Online).is_err() {
+                return;
+            }
+        }
+    });
+    Ok(())
+}
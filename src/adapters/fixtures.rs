@@ -0,0 +1,50 @@
+use crate::adapters::http::{Client, Request, Reqwest, Response};
+use crate::model::weather::Freshness;
+
+/// Recorded responses of the providers' endpoints
+const FIXTURES: [(&str, &str); 8] = [
+    ("/weather?", include_str!("../../tests/current.json")),
+    (
+        "/air_pollution?",
+        include_str!("../../tests/air_pollution.json"),
+    ),
+    ("/onecall?", include_str!("../../tests/onecall.json")),
+    (
+        "api.weather.com/v2/pws/observations/current",
+        include_str!("../../tests/weather_underground.json"),
+    ),
+    (
+        "api.netatmo.com/oauth2/token",
+        r#"{"access_token":"mock","refresh_token":"mock","expires_in":10800}"#,
+    ),
+    (
+        "api.netatmo.com/api/getstationsdata",
+        include_str!("../../tests/netatmo.json"),
+    ),
+    (
+        "/get_livedata_info",
+        include_str!("../../tests/ecowitt.json"),
+    ),
+    (
+        "air-quality-api.open-meteo.com/v1/air-quality?",
+        include_str!("../../tests/pollen.json"),
+    ),
+];
+
+/// Replays the recorded responses instead of querying the providers when testing,
+/// other endpoints are still queried
+pub struct Fixtures;
+
+impl Client for Fixtures {
+    fn send(&self, request: &Request) -> crate::Result<Response> {
+        let url = request.url();
+        let Some((_, body)) = FIXTURES.iter().find(|(endpoint, _)| url.contains(endpoint)) else {
+            return Reqwest.send(request);
+        };
+        Ok(Response {
+            status: 200,
+            body: body.to_string(),
+            freshness: Freshness::default(),
+        })
+    }
+}
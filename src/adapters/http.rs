@@ -1,31 +1,242 @@
-use std::time::Duration;
-use std::{process, thread};
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::process;
 
+use chrono::{DateTime, TimeDelta, Utc};
 use log::debug;
-use log::error;
+use reqwest::header::{HeaderMap, AGE, CACHE_CONTROL, EXPIRES, RETRY_AFTER};
 use serde::de::DeserializeOwned;
-use std::fmt::Debug;
-
-const RETRY_TIMEOUT: u64 = 15;
-
-pub fn get<T: Debug + DeserializeOwned>(url: &str) -> crate::Result<T> {
-    debug!("Querying {} ...", url);
-    let mut result = reqwest::blocking::get(url);
-    while let Err(err) = result {
-        println!("Unavailable");
-        error!("{}", err.to_string());
-        debug!("Retrying {} ...", url);
-        thread::sleep(Duration::from_secs(RETRY_TIMEOUT));
-        result = reqwest::blocking::get(url);
-    }
-
-    let response = result?;
-    debug!("HTTP {}", response.status().to_string());
-    if response.status().as_u16() == 401 {
-        println!("Invalid/unauthorized API key");
-        process::exit(1)
-    }
-    let t: T = response.json()?;
-    debug!("{:#?}", t);
-    Ok(t)
+
+use crate::model::weather::Freshness;
+
+/// The provider rejected the API key or the access token
+#[derive(Debug)]
+pub struct Unauthorized;
+
+impl Display for Unauthorized {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid/unauthorized API key")
+    }
+}
+
+impl Error for Unauthorized {}
+
+/// The provider asks to wait before requesting again
+#[derive(Debug)]
+pub struct RateLimited(pub DateTime<Utc>);
+
+impl Display for RateLimited {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Rate limited until {}", self.0)
+    }
+}
+
+impl Error for RateLimited {}
+
+/// An authorized request failed, the body tells why
+#[derive(Debug)]
+pub struct Status {
+    pub code: u16,
+    pub body: String,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "HTTP {}: {}", self.code, self.body)
+    }
+}
+
+impl Error for Status {}
+
+/// Sends the requests of the providers, which get it injected to replay recorded responses
+/// when testing
+pub trait Client {
+    fn send(&self, request: &Request) -> crate::Result<Response>;
+}
+
+pub enum Request<'a> {
+    Get {
+        url: &'a str,
+    },
+    GetAuthorized {
+        url: &'a str,
+        token: &'a str,
+    },
+    PostForm {
+        url: &'a str,
+        form: &'a [(&'a str, &'a str)],
+    },
+}
+
+impl Request<'_> {
+    pub fn url(&self) -> &str {
+        match self {
+            Request::Get { url }
+            | Request::GetAuthorized { url, .. }
+            | Request::PostForm { url, .. } => url,
+        }
+    }
+}
+
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+    pub freshness: Freshness,
+}
+
+impl dyn Client {
+    /// Exits if the API key is rejected, which won't change without changing the config
+    pub fn get<T: Debug + DeserializeOwned>(&self, url: &str) -> crate::Result<T> {
+        self.get_fresh(url).map(|(t, _)| t)
+    }
+
+    /// Like [get](Self::get), but also returns the freshness of the response
+    pub fn get_fresh<T: Debug + DeserializeOwned>(
+        &self,
+        url: &str,
+    ) -> crate::Result<(T, Freshness)> {
+        self.request(&Request::Get { url }).inspect_err(|err| {
+            if err.is::<Unauthorized>() {
+                println!("{}", err);
+                process::exit(1)
+            }
+        })
+    }
+
+    /// Fails with [Unauthorized] if the token is rejected, so it can be refreshed, and with
+    /// [Status] on other failures, since some providers reject tokens with other statuses
+    pub fn get_authorized<T: Debug + DeserializeOwned>(
+        &self,
+        url: &str,
+        token: &str,
+    ) -> crate::Result<(T, Freshness)> {
+        let response = self.send_checked(&Request::GetAuthorized { url, token })?;
+        if !(200..300).contains(&response.status) {
+            return Err(Status {
+                code: response.status,
+                body: response.body,
+            }
+            .into());
+        }
+        Self::parse(response)
+    }
+
+    pub fn post_form<T: Debug + DeserializeOwned>(
+        &self,
+        url: &str,
+        form: &[(&str, &str)],
+    ) -> crate::Result<T> {
+        self.request(&Request::PostForm { url, form })
+            .map(|(t, _)| t)
+    }
+
+    fn request<T: Debug + DeserializeOwned>(
+        &self,
+        request: &Request,
+    ) -> crate::Result<(T, Freshness)> {
+        Self::parse(self.send_checked(request)?)
+    }
+
+    /// Fails with [RateLimited] if the provider asks to retry later
+    fn send_checked(&self, request: &Request) -> crate::Result<Response> {
+        debug!("Querying {} ...", request.url());
+        // failing polls are retried by the schedule, which keeps handling events in between
+        let response = self.send(request)?;
+        debug!("HTTP {}", response.status);
+        if response.status == 401 {
+            return Err(Unauthorized.into());
+        }
+        if !(200..300).contains(&response.status) {
+            if let Some(retry_after) = response.freshness.retry_after {
+                return Err(RateLimited(retry_after).into());
+            }
+        }
+        Ok(response)
+    }
+
+    fn parse<T: Debug + DeserializeOwned>(response: Response) -> crate::Result<(T, Freshness)> {
+        let t: T = serde_json::from_str(&response.body)?;
+        debug!("{:#?}", t);
+        Ok((t, response.freshness))
+    }
+}
+
+/// Queries the providers' endpoints
+pub struct Reqwest;
+
+impl Client for Reqwest {
+    fn send(&self, request: &Request) -> crate::Result<Response> {
+        let client = reqwest::blocking::Client::new();
+        let response = match request {
+            Request::Get { url } => client.get(*url).send()?,
+            Request::GetAuthorized { url, token } => client.get(*url).bearer_auth(token).send()?,
+            Request::PostForm { url, form } => client.post(*url).form(form).send()?,
+        };
+        Ok(Response {
+            status: response.status().as_u16(),
+            freshness: freshness(response.headers(), Utc::now()),
+            body: response.text()?,
+        })
+    }
+}
+
+/// What the headers of a response tell about when to request again
+fn freshness(headers: &HeaderMap, now: DateTime<Utc>) -> Freshness {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let seconds = |value: &str| value.trim().parse().ok().map(TimeDelta::seconds);
+    let date = |value: &str| {
+        DateTime::parse_from_rfc2822(value.trim())
+            .ok()
+            .map(|date| date.with_timezone(&Utc))
+    };
+
+    let age = header(AGE).and_then(seconds).unwrap_or_default();
+    let max_age = header(CACHE_CONTROL).and_then(|value| {
+        value
+            .split(',')
+            .find_map(|directive| directive.trim().strip_prefix("max-age="))
+            .and_then(seconds)
+    });
+    Freshness {
+        expires: max_age
+            .map(|max_age| now + max_age - age)
+            .or_else(|| header(EXPIRES).and_then(date)),
+        retry_after: header(RETRY_AFTER).and_then(|value| {
+            seconds(value)
+                .map(|delay| now + delay)
+                .or_else(|| date(value))
+        }),
+    }
+}
+
+#[test]
+fn cache_headers() {
+    use reqwest::header::HeaderValue;
+
+    let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=600"),
+    );
+    headers.insert(AGE, HeaderValue::from_static("100"));
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+    let first = freshness(&headers, now);
+    assert_eq!(first.expires, Some(now + TimeDelta::seconds(500)));
+    assert_eq!(first.retry_after, Some(now + TimeDelta::seconds(120)));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        EXPIRES,
+        HeaderValue::from_static("Tue, 14 Nov 2023 22:16:40 GMT"),
+    );
+    headers.insert(
+        RETRY_AFTER,
+        HeaderValue::from_static("Tue, 14 Nov 2023 22:23:20 GMT"),
+    );
+    let merged = first.merge(freshness(&headers, now));
+    assert_eq!(merged.expires, Some(now + TimeDelta::seconds(200)));
+    assert_eq!(merged.retry_after, Some(now + TimeDelta::seconds(600)));
+
+    assert_eq!(freshness(&HeaderMap::new(), now), Freshness::default());
 }
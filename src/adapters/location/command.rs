@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::adapters::command;
+use crate::model::config::CommandConfig;
+use crate::model::location::{CurrentLocation, Location};
+
+/// Runs an external program printing the location as JSON, e.g.
+/// `{"city": "London", "lat": 51.5074, "lon": -0.1278}`
+pub struct Command {
+    config: CommandConfig,
+}
+
+impl Command {
+    pub fn new(config: &CommandConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+}
+
+impl CurrentLocation for Command {
+    fn location(&self) -> crate::Result<Location> {
+        if self.config.command.is_empty() {
+            return Err("No location command".into());
+        }
+        let timeout = Duration::from_secs(self.config.timeout.0);
+        let output = command::run(&self.config.command, timeout)?;
+        let output: Output = serde_json::from_str(&output)
+            .map_err(|err| format!("Couldn't parse location command output: {}", err))?;
+        Ok(Location {
+            city: output.city,
+            lat: output.lat,
+            lon: output.lon,
+        })
+    }
+}
+
+/// Unlike a configured location, the coordinates are required
+#[derive(Debug, Deserialize)]
+struct Output {
+    city: Option<String>,
+    lat: f32,
+    lon: f32,
+}
+
+#[test]
+fn location() {
+    let config = CommandConfig {
+        command: r#"echo '{"city": "London", "lat": 51.5074, "lon": -0.1278}'"#.to_string(),
+        ..Default::default()
+    };
+    let location = Command::new(&config).location().unwrap();
+    assert_eq!(location.city.unwrap(), "London");
+    assert_eq!(location.lat, 51.5074);
+
+    let config = CommandConfig {
+        command: "echo London".to_string(),
+        ..Default::default()
+    };
+    assert!(Command::new(&config).location().is_err());
+
+    let config = CommandConfig {
+        command: r#"echo '{"city": "London"}'"#.to_string(),
+        ..Default::default()
+    };
+    assert!(Command::new(&config).location().is_err());
+}
@@ -1,34 +1,56 @@
+use serde::Deserialize;
+
 use crate::{
-    adapters::http,
+    adapters::http::Client,
     model::location::{CurrentLocation, Location},
 };
 
-pub struct IpApi;
+pub struct IpApi {
+    client: &'static dyn Client,
+}
 
 impl IpApi {
-    const URL: &'static str = "http://ip-api.com/json/?fields=city,lat,lon";
-    pub fn new() -> Self {
-        Self
+    const URL: &'static str = "http://ip-api.com/json/";
+    const FIELDS: &'static str = "?fields=status,message,city,lat,lon";
+    pub fn new(client: &'static dyn Client) -> Self {
+        Self { client }
+    }
+
+    fn query(&self, url: &str) -> crate::Result<Location> {
+        let response: Response = self.client.get(url)?;
+        if response.status != "success" {
+            let message = response.message.unwrap_or(response.status);
+            return Err(format!("Couldn't get location from ip-api: {}", message).into());
+        }
+        Ok(response.location)
     }
 }
 
 #[cfg(not(feature = "test"))]
 impl CurrentLocation for IpApi {
     fn location(&self) -> crate::Result<Location> {
-        http::get(Self::URL)
+        self.query(&format!("{}{}", Self::URL, Self::FIELDS))
     }
 }
 
 #[cfg(feature = "test")]
 impl CurrentLocation for IpApi {
     fn location(&self) -> crate::Result<Location> {
-        http::get("http://ip-api.com/json/24.48.0.1?fields=city,lat,lon")
+        self.query(&format!("{}24.48.0.1{}", Self::URL, Self::FIELDS))
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct Response {
+    status: String,
+    message: Option<String>,
+    #[serde(flatten)]
+    location: Location,
+}
+
 #[test]
 fn location() {
-    let location = IpApi::new().location();
+    let location = IpApi::new(&crate::adapters::http::Reqwest).location();
     assert!(location.is_ok());
     let location = location.unwrap();
 
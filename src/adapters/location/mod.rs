@@ -1,2 +1,4 @@
+pub mod command;
 pub mod ip_api;
 pub mod manual;
+pub mod network;
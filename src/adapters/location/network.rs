@@ -0,0 +1,123 @@
+use std::fs;
+use std::net::Ipv4Addr;
+
+use log::debug;
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::adapters::connectivity::{NETWORK_MANAGER, NETWORK_MANAGER_PATH};
+
+use crate::model::location::{CurrentLocation, Location, NetworkLocation};
+
+const ROUTE_PATH: &str = "/proc/net/route";
+const ARP_PATH: &str = "/proc/net/arp";
+const WIRELESS: &str = "org.freedesktop.NetworkManager.Device.Wireless";
+const ACCESS_POINT: &str = "org.freedesktop.NetworkManager.AccessPoint";
+
+pub struct Network {
+    networks: Vec<NetworkLocation>,
+}
+
+impl Network {
+    pub fn new(networks: &[NetworkLocation]) -> Self {
+        Self {
+            networks: networks.to_vec(),
+        }
+    }
+}
+
+impl CurrentLocation for Network {
+    fn location(&self) -> crate::Result<Location> {
+        let ssid = ssid();
+        let gateway_mac = gateway_mac();
+        debug!(
+            "Connected to SSID {:?}, gateway MAC {:?}",
+            ssid, gateway_mac
+        );
+
+        self.networks
+            .iter()
+            .find(|network| network.matches(ssid.as_deref(), gateway_mac.as_deref()))
+            .map(|network| network.location.clone())
+            .ok_or_else(|| "Current network doesn't match any configured network".into())
+    }
+}
+
+/// The SSID of the access point a WiFi device is connected to, as announced by NetworkManager
+fn ssid() -> Option<String> {
+    active_ssid().unwrap_or_else(|err| {
+        debug!("Error querying NetworkManager for the SSID: {}", err);
+        None
+    })
+}
+
+fn active_ssid() -> crate::Result<Option<String>> {
+    let connection = Connection::system()?;
+    let network_manager = Proxy::new(
+        &connection,
+        NETWORK_MANAGER,
+        NETWORK_MANAGER_PATH,
+        NETWORK_MANAGER,
+    )?;
+    let devices: Vec<OwnedObjectPath> = network_manager.call("GetDevices", &())?;
+    for device in devices {
+        let wireless = Proxy::new(&connection, NETWORK_MANAGER, device, WIRELESS)?;
+        // only WiFi devices implement the interface
+        let Ok(access_point) = wireless.get_property::<OwnedObjectPath>("ActiveAccessPoint") else {
+            continue;
+        };
+        if access_point.as_str() == "/" {
+            continue;
+        }
+        let access_point = Proxy::new(&connection, NETWORK_MANAGER, access_point, ACCESS_POINT)?;
+        let ssid: Vec<u8> = access_point.get_property("Ssid")?;
+        return Ok(Some(String::from_utf8_lossy(&ssid).into_owned()));
+    }
+    Ok(None)
+}
+
+fn gateway_mac() -> Option<String> {
+    let gateway = default_gateway(&fs::read_to_string(ROUTE_PATH).ok()?)?;
+    mac(&fs::read_to_string(ARP_PATH).ok()?, gateway)
+}
+
+fn default_gateway(route: &str) -> Option<Ipv4Addr> {
+    route.lines().skip(1).find_map(|line| {
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        match columns.as_slice() {
+            [_, "00000000", gateway, ..] => {
+                let gateway = u32::from_str_radix(gateway, 16).ok()?;
+                // the kernel prints the address in host byte order
+                Some(Ipv4Addr::from(gateway.to_ne_bytes()))
+            }
+            _ => None,
+        }
+    })
+}
+
+fn mac(arp: &str, ip: Ipv4Addr) -> Option<String> {
+    let ip = ip.to_string();
+    arp.lines().skip(1).find_map(|line| {
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        match columns.as_slice() {
+            [address, _, _, mac, ..] if *address == ip => Some(mac.to_string()),
+            _ => None,
+        }
+    })
+}
+
+#[test]
+fn gateway() {
+    let route =
+        "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+wlp2s0\t0000A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\t0\t0\t0
+wlp2s0\t00000000\t0100A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0";
+    let arp = "IP address       HW type     Flags       HW address            Mask     Device
+192.168.0.42     0x1         0x2         11:22:33:44:55:66     *        wlp2s0
+192.168.0.1      0x1         0x2         aa:bb:cc:dd:ee:ff     *        wlp2s0";
+
+    let gateway = default_gateway(route);
+    assert_eq!(gateway, Some(Ipv4Addr::new(192, 168, 0, 1)));
+    assert_eq!(mac(arp, gateway.unwrap()).unwrap(), "aa:bb:cc:dd:ee:ff");
+    assert_eq!(default_gateway(""), None);
+}
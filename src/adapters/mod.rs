@@ -1,4 +1,19 @@
+pub mod cache;
+pub mod clock;
+mod command;
 pub mod config;
-mod http;
+pub mod connectivity;
+#[cfg(any(test, feature = "test"))]
+pub mod fixtures;
+pub mod http;
 pub mod location;
+pub mod pid_file;
+pub mod pollen;
+pub mod power;
+pub mod recorder;
+mod runtime;
+pub mod sensor;
+pub mod session;
+pub mod signals;
+pub mod socket;
 pub mod weather;
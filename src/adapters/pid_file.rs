@@ -0,0 +1,77 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process;
+
+use log::{debug, warn};
+
+use crate::adapters::runtime;
+use crate::APP_NAME;
+
+/// Writes the PID of this process, so `wedder refresh` can still signal it without the socket
+pub fn create() -> crate::Result<()> {
+    let path = path().ok_or("Couldn't get PID file path")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, process::id().to_string())?;
+    debug!("Wrote PID file {:?}", path);
+    Ok(())
+}
+
+/// Removes the PID file unless another wedder has taken it over since
+pub fn remove() {
+    let Some(path) = path() else { return };
+    if read().ok() != Some(process::id() as i32) {
+        return;
+    }
+    if let Err(err) = fs::remove_file(&path) {
+        warn!("Couldn't remove PID file {:?}: {}", path, err);
+    }
+}
+
+/// Sends the signal to the running wedder
+pub fn signal(signal: libc::c_int) -> crate::Result<()> {
+    let pid = read().map_err(|_| "wedder isn't running")?;
+    // a stale PID file may name an unrelated process which reused the PID
+    if !is_wedder(pid) {
+        return Err("wedder isn't running".into());
+    }
+    // SAFETY: kill has no memory safety preconditions
+    if unsafe { libc::kill(pid, signal) } != 0 {
+        let err = io::Error::last_os_error();
+        return Err(format!("Couldn't signal wedder with PID {}: {}", pid, err).into());
+    }
+    Ok(())
+}
+
+fn read() -> crate::Result<i32> {
+    let path = path().ok_or("Couldn't get PID file path")?;
+    Ok(fs::read_to_string(path)?.trim().parse()?)
+}
+
+fn is_wedder(pid: i32) -> bool {
+    fs::read_to_string(format!("/proc/{}/comm", pid)).is_ok_and(|comm| comm.trim_end() == APP_NAME)
+}
+
+fn path() -> Option<PathBuf> {
+    runtime::path(&format!("{}.pid", APP_NAME))
+}
+
+#[test]
+fn pid_file() {
+    create().unwrap();
+    assert_eq!(read().unwrap(), process::id() as i32);
+    remove();
+    assert!(path().is_some_and(|path| !path.exists()));
+    // signal 0 only checks whether the process exists
+    assert!(signal(0).is_err());
+
+    // a stale PID reused by another process
+    let mut other = process::Command::new("sleep").arg("10").spawn().unwrap();
+    fs::write(path().unwrap(), other.id().to_string()).unwrap();
+    assert!(signal(0).is_err());
+    other.kill().unwrap();
+    other.wait().unwrap();
+    fs::remove_file(path().unwrap()).unwrap();
+}
@@ -0,0 +1,98 @@
+use serde::Deserialize;
+
+use crate::adapters::http::Client;
+use crate::model::location::Location;
+use crate::model::pollen::{CurrentPollen, Grains, Pollen};
+
+pub struct OpenMeteo {
+    client: &'static dyn Client,
+}
+
+impl OpenMeteo {
+    const URL: &'static str = "https://air-quality-api.open-meteo.com/v1/air-quality?";
+    const FIELDS: &'static str = "alder_pollen,birch_pollen,grass_pollen,\
+        mugwort_pollen,olive_pollen,ragweed_pollen";
+
+    pub fn new(client: &'static dyn Client) -> Self {
+        Self { client }
+    }
+}
+
+impl CurrentPollen for OpenMeteo {
+    fn pollen(&self, location: &Location) -> crate::Result<Box<dyn Pollen>> {
+        let url = format!(
+            "{}latitude={}&longitude={}&current={}",
+            Self::URL,
+            location.lat,
+            location.lon,
+            Self::FIELDS
+        );
+        let response: Response = self.client.get(&url)?;
+        Ok(Box::new(response.current))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    current: OpenMeteoPollen,
+}
+
+/// Only available in Europe during the pollen season, null otherwise
+#[derive(Debug, Deserialize)]
+struct OpenMeteoPollen {
+    alder_pollen: Option<Grains>,
+    birch_pollen: Option<Grains>,
+    grass_pollen: Option<Grains>,
+    mugwort_pollen: Option<Grains>,
+    olive_pollen: Option<Grains>,
+    ragweed_pollen: Option<Grains>,
+}
+
+impl Pollen for OpenMeteoPollen {
+    fn alder(&self) -> Option<Grains> {
+        self.alder_pollen
+    }
+
+    fn birch(&self) -> Option<Grains> {
+        self.birch_pollen
+    }
+
+    fn grass(&self) -> Option<Grains> {
+        self.grass_pollen
+    }
+
+    fn mugwort(&self) -> Option<Grains> {
+        self.mugwort_pollen
+    }
+
+    fn olive(&self) -> Option<Grains> {
+        self.olive_pollen
+    }
+
+    fn ragweed(&self) -> Option<Grains> {
+        self.ragweed_pollen
+    }
+}
+
+#[test]
+fn pollen() {
+    use crate::adapters::fixtures::Fixtures;
+
+    let location = Location {
+        city: None,
+        lat: 0.0,
+        lon: 0.0,
+    };
+    let pollen = OpenMeteo::new(&Fixtures).pollen(&location);
+    assert!(pollen.is_ok());
+    let pollen = pollen.unwrap();
+
+    assert_eq!(pollen.alder().unwrap().0, 0.0);
+    assert_eq!(pollen.birch().unwrap().0, 42.3);
+    assert_eq!(pollen.grass().unwrap().0, 3.1);
+    assert_eq!(pollen.olive(), None);
+    assert_eq!(
+        pollen.level(),
+        Some(crate::model::pollen::PollenLevel::Moderate)
+    );
+}
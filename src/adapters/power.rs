@@ -0,0 +1,58 @@
+use std::fs;
+use std::path::Path;
+
+const POWER_SUPPLY: &str = "/sys/class/power_supply";
+
+/// Whether the machine runs on battery, which a machine without one never does
+pub fn on_battery() -> bool {
+    on_battery_in(Path::new(POWER_SUPPLY))
+}
+
+fn on_battery_in(dir: &Path) -> bool {
+    let Ok(supplies) = fs::read_dir(dir) else {
+        return false;
+    };
+    let mut discharging = false;
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        let read = |name| {
+            fs::read_to_string(path.join(name))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+        match read("type").as_str() {
+            "Mains" | "USB" if read("online") == "1" => return false,
+            // batteries of devices like mice have the scope Device
+            "Battery" if read("scope") != "Device" => {
+                discharging |= read("status") == "Discharging"
+            }
+            _ => {}
+        }
+    }
+    discharging
+}
+
+#[test]
+fn battery() {
+    let dir = std::env::temp_dir().join(format!("wedder-power-{}", std::process::id()));
+    let supply = |name: &str, values: &[(&str, &str)]| {
+        let path = dir.join(name);
+        fs::create_dir_all(&path).unwrap();
+        for (file, value) in values {
+            fs::write(path.join(file), format!("{}\n", value)).unwrap();
+        }
+    };
+    assert!(!on_battery_in(&dir));
+
+    supply("BAT0", &[("type", "Battery"), ("status", "Discharging")]);
+    supply(
+        "hidpp_battery_0",
+        &[("type", "Battery"), ("scope", "Device")],
+    );
+    assert!(on_battery_in(&dir));
+
+    supply("AC", &[("type", "Mains"), ("online", "1")]);
+    assert!(!on_battery_in(&dir));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
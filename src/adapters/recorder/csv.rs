@@ -0,0 +1,126 @@
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+
+use log::debug;
+
+use crate::model::record::{Record, Recorder};
+
+pub struct Csv {
+    path: PathBuf,
+    /// Whether the header of the file matches the current columns
+    migrated: bool,
+}
+
+impl Csv {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            migrated: false,
+        }
+    }
+
+    /// Rewrites a history recorded with other columns, as appended rows have to match the header
+    fn migrate(&self) -> crate::Result<()> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+        let mut reader = csv::Reader::from_path(&self.path)?;
+        let header = reader.headers()?;
+        if header.is_empty() || header.iter().eq(Record::columns()) {
+            return Ok(());
+        }
+        // missing columns are read as empty values
+        let records = reader.deserialize().collect::<Result<Vec<Record>, _>>()?;
+        let migrated = self.path.with_extension("csv.tmp");
+        let mut writer = csv::Writer::from_path(&migrated)?;
+        for record in &records {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+        fs::rename(&migrated, &self.path)?;
+        debug!("Migrated {} records of {:?}", records.len(), self.path);
+        Ok(())
+    }
+}
+
+impl Recorder for Csv {
+    fn record(&mut self, record: &Record) -> crate::Result<()> {
+        if !self.migrated {
+            self.migrate()?;
+            self.migrated = true;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let is_empty = file.metadata()?.len() == 0;
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(is_empty)
+            .from_writer(file);
+        writer.serialize(record)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn records(&self, start: i64, end: i64) -> crate::Result<Vec<Record>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let mut reader = csv::Reader::from_path(&self.path)?;
+        let mut records = Vec::new();
+        for record in reader.deserialize() {
+            let record: Record = record?;
+            if (start..end).contains(&record.time) {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+}
+
+#[test]
+fn csv() {
+    let path = std::env::temp_dir().join("wedder-test-history.csv");
+    let _ = std::fs::remove_file(&path);
+    let mut csv = Csv::new(path.clone());
+
+    let record = |time| Record {
+        time,
+        city: Some("Zürich, \"CH\"".to_string()),
+        temperature: Some(280.5),
+        ..Default::default()
+    };
+    csv.record(&record(10)).unwrap();
+    csv.record(&record(20)).unwrap();
+    csv.record(&record(30)).unwrap();
+
+    assert_eq!(csv.records(15, 30).unwrap(), vec![record(20)]);
+    assert_eq!(csv.records(0, 100).unwrap().len(), 3);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn migrate() {
+    let path = std::env::temp_dir().join("wedder-test-history-migrate.csv");
+    fs::write(
+        &path,
+        "time,city,lat,lon,temperature\n10,London,51.5,-0.1,280.5\n",
+    )
+    .unwrap();
+    let mut csv = Csv::new(path.clone());
+
+    let record = Record {
+        time: 20,
+        humidity: Some(60.0),
+        ..Default::default()
+    };
+    csv.record(&record).unwrap();
+
+    let records = csv.records(0, 100).unwrap();
+    assert_eq!(records[0].temperature, Some(280.5));
+    assert_eq!(records[0].humidity, None);
+    assert_eq!(records[1], record);
+    let header = fs::read_to_string(&path).unwrap();
+    assert!(header.starts_with(&Record::columns().join(",")));
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use crate::model::config::{RecorderConfig, RecorderFormat};
+use crate::model::record::Recorder;
+use crate::APP_NAME;
+
+pub mod csv;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+pub fn recorder(config: &RecorderConfig) -> crate::Result<Box<dyn Recorder>> {
+    let path = match config.path.as_str() {
+        "" => default_path(&config.format).ok_or("Couldn't get data path")?,
+        path => PathBuf::from(path),
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    match config.format {
+        RecorderFormat::Csv => Ok(Box::new(csv::Csv::new(path))),
+        #[cfg(feature = "sqlite")]
+        RecorderFormat::Sqlite => Ok(Box::new(sqlite::Sqlite::new(&path)?)),
+        #[cfg(not(feature = "sqlite"))]
+        RecorderFormat::Sqlite => Err("wedder was built without the sqlite feature".into()),
+    }
+}
+
+fn file_name(format: &RecorderFormat) -> &'static str {
+    match format {
+        RecorderFormat::Csv => "history.csv",
+        RecorderFormat::Sqlite => "history.sqlite",
+    }
+}
+
+#[cfg(not(any(test, feature = "test")))]
+fn default_path(format: &RecorderFormat) -> Option<PathBuf> {
+    let project = directories::ProjectDirs::from("rs", APP_NAME, APP_NAME)?;
+    Some(project.data_dir().join(file_name(format)))
+}
+
+// don't touch the user's history when testing
+#[cfg(any(test, feature = "test"))]
+fn default_path(format: &RecorderFormat) -> Option<PathBuf> {
+    Some(std::env::temp_dir().join(APP_NAME).join(file_name(format)))
+}
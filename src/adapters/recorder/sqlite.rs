@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use rusqlite::{params, Connection, Row, ToSql};
+
+use crate::model::record::{Record, Recorder};
+
+pub struct Sqlite {
+    connection: Connection,
+}
+
+impl Sqlite {
+    pub fn new(path: &Path) -> crate::Result<Self> {
+        let connection = Connection::open(path)?;
+        let values = Record::default()
+            .values()
+            .map(|(name, _)| format!("{} REAL", name))
+            .join(",\n");
+        connection.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS history (
+                time INTEGER NOT NULL,
+                city TEXT,
+                lat REAL NOT NULL,
+                lon REAL NOT NULL,
+                condition TEXT,
+                {}
+            )",
+                values
+            ),
+            [],
+        )?;
+        connection.execute(
+            "CREATE INDEX IF NOT EXISTS history_time ON history (time)",
+            [],
+        )?;
+        Self::migrate(&connection)?;
+        Ok(Self { connection })
+    }
+
+    /// Adds the columns of values which weren't recorded yet when the table was created
+    fn migrate(connection: &Connection) -> crate::Result<()> {
+        let columns = connection
+            .prepare("SELECT name FROM pragma_table_info('history')")?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<HashSet<String>>>()?;
+        for (name, _) in Record::default().values() {
+            if !columns.contains(name) {
+                connection.execute(&format!("ALTER TABLE history ADD COLUMN {} REAL", name), [])?;
+            }
+        }
+        Ok(())
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Record> {
+        let mut record = Record {
+            time: row.get(0)?,
+            city: row.get(1)?,
+            lat: row.get(2)?,
+            lon: row.get(3)?,
+            condition: row.get(4)?,
+            ..Default::default()
+        };
+        for (i, (_, value)) in record.values_mut().into_iter().enumerate() {
+            *value = row.get(5 + i)?;
+        }
+        Ok(record)
+    }
+}
+
+impl Recorder for Sqlite {
+    fn record(&mut self, record: &Record) -> crate::Result<()> {
+        let columns = Record::columns();
+        let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+        let sql = format!(
+            "INSERT INTO history ({}) VALUES ({})",
+            columns.join(", "),
+            placeholders.join(", ")
+        );
+        let values = record.values();
+        let mut params: Vec<&dyn ToSql> = vec![
+            &record.time,
+            &record.city,
+            &record.lat,
+            &record.lon,
+            &record.condition,
+        ];
+        params.extend(values.iter().map(|(_, value)| value as &dyn ToSql));
+        self.connection.execute(&sql, params.as_slice())?;
+        Ok(())
+    }
+
+    fn records(&self, start: i64, end: i64) -> crate::Result<Vec<Record>> {
+        let sql = format!(
+            "SELECT {} FROM history WHERE time >= ?1 AND time < ?2 ORDER BY time",
+            Record::columns().join(", ")
+        );
+        let mut statement = self.connection.prepare(&sql)?;
+        let records = statement
+            .query_map(params![start, end], Self::from_row)?
+            .collect::<rusqlite::Result<Vec<Record>>>()?;
+        Ok(records)
+    }
+}
+
+#[test]
+fn sqlite() {
+    let mut sqlite = Sqlite::new(Path::new(":memory:")).unwrap();
+
+    let record = |time| Record {
+        time,
+        city: Some("London".to_string()),
+        temperature: Some(280.5),
+        ..Default::default()
+    };
+    sqlite.record(&record(10)).unwrap();
+    sqlite.record(&record(20)).unwrap();
+    sqlite.record(&record(30)).unwrap();
+
+    assert_eq!(sqlite.records(15, 30).unwrap(), vec![record(20)]);
+    assert_eq!(sqlite.records(0, 100).unwrap().len(), 3);
+}
+
+#[test]
+fn migrate() {
+    let path = std::env::temp_dir().join("wedder-test-history-migrate.sqlite");
+    let _ = std::fs::remove_file(&path);
+    let connection = Connection::open(&path).unwrap();
+    connection
+        .execute(
+            "CREATE TABLE history (time INTEGER NOT NULL, city TEXT, lat REAL NOT NULL, \
+            lon REAL NOT NULL, condition TEXT, temperature REAL)",
+            [],
+        )
+        .unwrap();
+    connection
+        .execute(
+            "INSERT INTO history (time, lat, lon, temperature) VALUES (10, 0, 0, 280.5)",
+            [],
+        )
+        .unwrap();
+    drop(connection);
+
+    let mut sqlite = Sqlite::new(&path).unwrap();
+    let record = Record {
+        time: 20,
+        humidity: Some(60.0),
+        ..Default::default()
+    };
+    sqlite.record(&record).unwrap();
+    let records = sqlite.records(0, 100).unwrap();
+    assert_eq!(records[0].temperature, Some(280.5));
+    assert_eq!(records[1], record);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use crate::APP_NAME;
+
+/// Path of a file only existing while wedder is running, like its PID file or socket
+#[cfg(not(any(test, feature = "test")))]
+pub fn path(name: &str) -> Option<PathBuf> {
+    let project = directories::ProjectDirs::from("rs", APP_NAME, APP_NAME)?;
+    let dir = project.runtime_dir().unwrap_or_else(|| project.cache_dir());
+    Some(dir.join(name))
+}
+
+// don't interfere with a wedder the user is running when testing
+#[cfg(any(test, feature = "test"))]
+pub fn path(name: &str) -> Option<PathBuf> {
+    Some(
+        std::env::temp_dir()
+            .join(APP_NAME)
+            .join(format!("{}-{}", std::process::id(), name)),
+    )
+}
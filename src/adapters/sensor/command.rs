@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use crate::adapters::command;
+use crate::model::config::{CommandConfig, TemperatureUnit};
+use crate::model::sensor::{Sensor, SensorReading};
+
+/// Runs a shell command printing the reading
+pub struct Command {
+    config: CommandConfig,
+    unit: TemperatureUnit,
+}
+
+impl Command {
+    pub fn new(config: &CommandConfig, unit: &TemperatureUnit) -> Self {
+        Self {
+            config: config.clone(),
+            unit: unit.clone(),
+        }
+    }
+}
+
+impl Sensor for Command {
+    fn reading(&self) -> crate::Result<SensorReading> {
+        let timeout = Duration::from_secs(self.config.timeout.0);
+        let output = command::run(&self.config.command, timeout)?;
+        SensorReading::parse(&output, &self.unit)
+    }
+}
+
+#[test]
+fn command() {
+    let config = CommandConfig {
+        command: "echo 21.5 40".to_string(),
+        ..Default::default()
+    };
+    let sensor = Command::new(&config, &TemperatureUnit::Celsius);
+    let reading = sensor.reading().unwrap();
+    assert_eq!(reading.humidity.unwrap().0, 40.0);
+}
@@ -0,0 +1,27 @@
+use std::fs;
+
+use crate::model::config::TemperatureUnit;
+use crate::model::sensor::{Sensor, SensorReading};
+
+/// Reads a file like /sys/bus/w1/devices/28-*/w1_slave of a DS18B20
+pub struct File {
+    path: String,
+    unit: TemperatureUnit,
+}
+
+impl File {
+    pub fn new(path: &str, unit: &TemperatureUnit) -> Self {
+        Self {
+            path: path.to_string(),
+            unit: unit.clone(),
+        }
+    }
+}
+
+impl Sensor for File {
+    fn reading(&self) -> crate::Result<SensorReading> {
+        let content = fs::read_to_string(&self.path)
+            .map_err(|err| format!("Couldn't read sensor file {}: {}", self.path, err))?;
+        SensorReading::parse(&content, &self.unit)
+    }
+}
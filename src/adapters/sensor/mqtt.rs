@@ -0,0 +1,318 @@
+use std::io::{Error, ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+
+use crate::model::config::{MqttConfig, TemperatureUnit};
+use crate::model::sensor::{Sensor, SensorReading};
+
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+const RECONNECT_DELAY: Duration = Duration::from_secs(15);
+/// How long to wait for the first message after starting
+const FIRST_MESSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const PINGREQ: u8 = 0xc0;
+
+#[derive(Default)]
+struct State {
+    reading: Option<SensorReading>,
+    error: Option<String>,
+}
+
+/// Minimal MQTT 3.1.1 client keeping the latest message of a topic in the background
+pub struct Mqtt {
+    state: Arc<(Mutex<State>, Condvar)>,
+    /// Ends the listener once the sensor is dropped, e.g. on reloading the config
+    stopped: Arc<AtomicBool>,
+}
+
+impl Mqtt {
+    pub fn new(config: &MqttConfig, unit: &TemperatureUnit) -> Self {
+        let state = Arc::new((Mutex::new(State::default()), Condvar::new()));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let listener = Listener {
+            config: config.clone(),
+            unit: unit.clone(),
+            state: state.clone(),
+            stopped: stopped.clone(),
+        };
+        thread::spawn(move || listener.listen());
+        Self { state, stopped }
+    }
+}
+
+impl Drop for Mqtt {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Sensor for Mqtt {
+    fn reading(&self) -> crate::Result<SensorReading> {
+        let (state, received) = &*self.state;
+        let state = state.lock().map_err(|_| "MQTT listener panicked")?;
+        let (state, _) = received
+            .wait_timeout_while(state, FIRST_MESSAGE_TIMEOUT, |state| {
+                state.reading.is_none()
+            })
+            .map_err(|_| "MQTT listener panicked")?;
+        match (state.reading, &state.error) {
+            (Some(reading), _) => Ok(reading),
+            (None, Some(error)) => Err(error.clone().into()),
+            (None, None) => Err("No MQTT message received yet".into()),
+        }
+    }
+}
+
+struct Listener {
+    config: MqttConfig,
+    unit: TemperatureUnit,
+    state: Arc<(Mutex<State>, Condvar)>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl Listener {
+    fn listen(self) {
+        while !self.stopped() {
+            let Err(err) = self.subscribe() else {
+                break;
+            };
+            warn!(
+                "MQTT connection to {}:{} failed: {}",
+                self.config.host, self.config.port, err
+            );
+            if let Ok(mut state) = self.state.0.lock() {
+                state.error = Some(err.to_string());
+            }
+            thread::sleep(RECONNECT_DELAY);
+        }
+        debug!("Stopped listening to MQTT topic {}", self.config.topic);
+    }
+
+    fn stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Keeps the latest message of the topic until the connection fails or the sensor is dropped
+    fn subscribe(&self) -> crate::Result<()> {
+        let mut stream = TcpStream::connect((self.config.host.as_str(), self.config.port))?;
+        // pings within the keep alive even if a message arrives just before a ping is due
+        stream.set_read_timeout(Some(KEEP_ALIVE / 4))?;
+
+        stream.write_all(&self.connect_packet())?;
+        let (header, body) = read_packet(&mut stream)?;
+        if header & 0xf0 != CONNACK || body.get(1) != Some(&0) {
+            return Err(format!("Connection refused with code {:?}", body.get(1)).into());
+        }
+        stream.write_all(&subscribe_packet(&self.config.topic))?;
+        debug!("Subscribed to MQTT topic {}", self.config.topic);
+
+        // the keep alive only counts packets sent by the client, so frequent messages don't
+        // keep the connection alive
+        let mut last_write = Instant::now();
+        loop {
+            if last_write.elapsed() >= KEEP_ALIVE / 2 {
+                stream.write_all(&[PINGREQ, 0])?;
+                last_write = Instant::now();
+            }
+            let (header, body) = match read_packet(&mut stream) {
+                Ok(packet) => packet,
+                Err(_) if self.stopped() => return Ok(()),
+                Err(err) if is_timeout(&err) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            match header & 0xf0 {
+                PUBLISH => self.publish(header, &body),
+                SUBACK if body.last() == Some(&0x80) => {
+                    return Err(format!("Subscribing to {} failed", self.config.topic).into())
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn publish(&self, header: u8, body: &[u8]) {
+        let Some(payload) = payload(header, body) else {
+            warn!("Ignoring malformed MQTT message");
+            return;
+        };
+        let payload = String::from_utf8_lossy(payload);
+        debug!("Received MQTT message {}", payload);
+        match SensorReading::parse(&payload, &self.unit) {
+            Ok(reading) => {
+                let (state, received) = &*self.state;
+                if let Ok(mut state) = state.lock() {
+                    state.reading = Some(reading);
+                    state.error = None;
+                    received.notify_all();
+                }
+            }
+            Err(err) => warn!("{}", err),
+        }
+    }
+
+    fn connect_packet(&self) -> Vec<u8> {
+        let mut flags = 0x02; // clean session
+        let mut payload = Vec::new();
+        encode_string(&mut payload, &format!("wedder-{}", process::id()));
+        if !self.config.username.is_empty() {
+            flags |= 0x80;
+            encode_string(&mut payload, &self.config.username);
+        }
+        if !self.config.password.is_empty() {
+            flags |= 0x40;
+            encode_string(&mut payload, &self.config.password);
+        }
+
+        let mut body = Vec::new();
+        encode_string(&mut body, "MQTT");
+        body.push(4); // protocol level 3.1.1
+        body.push(flags);
+        body.extend_from_slice(&(KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+        body.extend(payload);
+        packet(CONNECT, &body)
+    }
+}
+
+fn subscribe_packet(topic: &str) -> Vec<u8> {
+    let mut body = 1u16.to_be_bytes().to_vec(); // packet identifier
+    encode_string(&mut body, topic);
+    body.push(0); // QoS
+    packet(SUBSCRIBE, &body)
+}
+
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    // variable length encoding of the remaining length
+    let mut length = body.len();
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+fn encode_string(buffer: &mut Vec<u8>, string: &str) {
+    buffer.extend_from_slice(&(string.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(string.as_bytes());
+}
+
+/// Waits for the next packet, timing out only if none started to arrive
+fn read_packet(stream: &mut impl Read) -> std::io::Result<(u8, Vec<u8>)> {
+    let mut byte = [0];
+    stream.read_exact(&mut byte)?;
+    let header = byte[0];
+    // the rest may already be partially read, so it can't be resumed after pinging
+    let body = read_body(stream).map_err(|err| {
+        if is_timeout(&err) {
+            Error::other("Timed out in the middle of an MQTT packet")
+        } else {
+            err
+        }
+    })?;
+    Ok((header, body))
+}
+
+fn read_body(stream: &mut impl Read) -> std::io::Result<Vec<u8>> {
+    let mut byte = [0];
+    let mut length = 0;
+    for shift in (0..4).map(|i| i * 7) {
+        stream.read_exact(&mut byte)?;
+        length |= ((byte[0] & 0x7f) as usize) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+    }
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body)?;
+    Ok(body)
+}
+
+/// Skips the topic and the packet identifier of a PUBLISH
+fn payload(header: u8, body: &[u8]) -> Option<&[u8]> {
+    let topic_length = u16::from_be_bytes([*body.first()?, *body.get(1)?]) as usize;
+    let qos = (header >> 1) & 0x03;
+    let offset = 2 + topic_length + if qos > 0 { 2 } else { 0 };
+    body.get(offset..)
+}
+
+fn is_timeout(err: &std::io::Error) -> bool {
+    matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+#[test]
+fn mqtt() {
+    use std::net::TcpListener;
+
+    // fake broker accepting the subscription and publishing a single message
+    let broker = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = broker.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let (mut stream, _) = broker.accept().unwrap();
+        let (header, _) = read_packet(&mut stream).unwrap();
+        assert_eq!(header, CONNECT);
+        stream.write_all(&packet(CONNACK, &[0, 0])).unwrap();
+        let (header, body) = read_packet(&mut stream).unwrap();
+        assert_eq!(header, SUBSCRIBE);
+        assert!(String::from_utf8_lossy(&body).contains("home/living_room"));
+        stream.write_all(&packet(SUBACK, &[0, 1, 0])).unwrap();
+
+        let mut publish = Vec::new();
+        encode_string(&mut publish, "home/living_room");
+        publish.extend_from_slice(br#"{"temperature": 21.5, "humidity": 40}"#);
+        stream.write_all(&packet(PUBLISH, &publish)).unwrap();
+        thread::sleep(Duration::from_secs(10));
+    });
+
+    let config = MqttConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        topic: "home/living_room".to_string(),
+        ..Default::default()
+    };
+    let reading = Mqtt::new(&config, &TemperatureUnit::Celsius)
+        .reading()
+        .unwrap();
+    assert_eq!(reading.temperature.unwrap().0, 21.5 + 273.15);
+    assert_eq!(reading.humidity.unwrap().0, 40.0);
+}
+
+#[test]
+fn timeouts() {
+    /// Returns the bytes and then times out like a socket with a read timeout
+    struct Stalling<'a>(&'a [u8]);
+
+    impl Read for Stalling<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(ErrorKind::WouldBlock.into());
+            }
+            self.0.read(buf)
+        }
+    }
+
+    // waiting for the next packet
+    let err = read_packet(&mut Stalling(&[])).unwrap_err();
+    assert!(is_timeout(&err));
+    // stalled after the fixed header
+    let err = read_packet(&mut Stalling(&[PUBLISH, 4, 0])).unwrap_err();
+    assert!(!is_timeout(&err));
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+use log::{debug, warn};
+use zbus::blocking::{Connection, MessageIterator, Proxy};
+use zbus::message::{Message, Type};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue};
+use zbus::MatchRule;
+
+use crate::model::event::Event;
+
+const LOGIND: &str = "org.freedesktop.login1";
+const LOGIND_PATH: &str = "/org/freedesktop/login1";
+const LOGIND_MANAGER: &str = "org.freedesktop.login1.Manager";
+
+/// Whether the session of the user is away, in which case nobody looks at the weather
+#[derive(Default)]
+struct State {
+    locked: bool,
+    sleeping: bool,
+}
+
+impl State {
+    fn away(&self) -> bool {
+        self.locked || self.sleeping
+    }
+}
+
+/// Turns the session being locked or suspended and coming back into events
+/// in a background thread, as announced by logind
+pub fn watch(events: Sender<Event>) -> crate::Result<()> {
+    let connection = Connection::system()?;
+    let manager = Proxy::new(&connection, LOGIND, LOGIND_PATH, LOGIND_MANAGER)?;
+    let session: OwnedObjectPath = manager.call("GetSession", &("auto",))?;
+    debug!("Watching session {}", session.as_str());
+    let rule = MatchRule::builder()
+        .msg_type(Type::Signal)
+        .sender(LOGIND)?
+        .build();
+    let messages = MessageIterator::for_match_rule(rule, &connection, None)?;
+
+    thread::spawn(move || {
+        let mut state = State::default();
+        for message in messages {
+            let was_away = state.away();
+            let result = message
+                .map_err(Into::into)
+                .and_then(|message| update(&mut state, &message, &session));
+            if let Err(err) = result {
+                warn!("Error handling logind signal: {}", err);
+                continue;
+            }
+            let event = match (was_away, state.away()) {
+                (false, true) => Event::Pause,
+                (true, false) => Event::Resume,
+                _ => continue,
+            };
+            if events.send(event).is_err() {
+                return;
+            }
+        }
+    });
+    Ok(())
+}
+
+fn update(state: &mut State, message: &Message, session: &OwnedObjectPath) -> crate::Result<()> {
+    let header = message.header();
+    let of_session = header
+        .path()
+        .is_some_and(|path| path.as_str() == session.as_str());
+    match header.member().map(|member| member.as_str()) {
+        Some("PrepareForSleep") => state.sleeping = message.body().deserialize()?,
+        Some("Lock") if of_session => state.locked = true,
+        Some("Unlock") if of_session => state.locked = false,
+        // screen lockers that don't listen to Lock only set the hint
+        Some("PropertiesChanged") if of_session => {
+            let (_, changed, _): (String, HashMap<String, OwnedValue>, Vec<String>) =
+                message.body().deserialize()?;
+            if let Some(locked) = changed.get("LockedHint") {
+                state.locked = bool::try_from(locked)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
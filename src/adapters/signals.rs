@@ -0,0 +1,41 @@
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+use std::{process, thread};
+
+use log::{debug, warn};
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM, SIGUSR1, SIGUSR2};
+use signal_hook::iterator::Signals;
+
+use crate::adapters::{pid_file, socket};
+use crate::model::event::Event;
+
+/// How long a poll may delay exiting, e.g. while waiting for a slow provider
+const EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Turns signals into events in a background thread
+pub fn forward(events: Sender<Event>) -> crate::Result<()> {
+    let mut signals = Signals::new([SIGHUP, SIGUSR1, SIGUSR2, SIGTERM, SIGINT])?;
+    thread::spawn(move || {
+        for signal in signals.forever() {
+            debug!("Received signal {}", signal);
+            let event = match signal {
+                SIGHUP => Event::Reload,
+                SIGUSR1 => Event::Refresh,
+                SIGUSR2 => Event::NextFormat,
+                SIGTERM | SIGINT => Event::Exit,
+                _ => continue,
+            };
+            if events.send(event).is_err() {
+                return;
+            }
+            if event == Event::Exit {
+                thread::sleep(EXIT_TIMEOUT);
+                warn!("Exiting without finishing the current poll");
+                pid_file::remove();
+                socket::remove();
+                process::exit(0);
+            }
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+use log::{debug, warn};
+
+use crate::adapters::runtime;
+use crate::model::event::Event;
+use crate::APP_NAME;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Whether this process owns the socket and has to remove it
+static LISTENING: AtomicBool = AtomicBool::new(false);
+
+/// Accepts commands of clients like `wedder next-format` in a background thread
+pub fn listen(events: Sender<Event>) -> crate::Result<()> {
+    let path = path()?;
+    if UnixStream::connect(&path).is_ok() {
+        return Err("Another wedder is already listening".into());
+    }
+    // left behind by a wedder that didn't exit cleanly
+    let _ = fs::remove_file(&path);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    LISTENING.store(true, Ordering::SeqCst);
+    debug!("Listening on {:?}", path);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream
+                .map_err(Into::into)
+                .and_then(|stream| handle(stream, &events));
+            if let Err(err) = result {
+                warn!("Error handling socket command: {}", err);
+            }
+        }
+    });
+    Ok(())
+}
+
+fn handle(stream: UnixStream, events: &Sender<Event>) -> crate::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    let mut command = String::new();
+    BufReader::new(&stream).read_line(&mut command)?;
+    debug!("Received command {}", command.trim());
+    let event = match command.trim() {
+        "next-format" => Event::NextFormat,
+        "refresh" => Event::Refresh,
+        "reload" => Event::Reload,
+        command => {
+            writeln!(&stream, "Unknown command {}", command)?;
+            return Ok(());
+        }
+    };
+    events.send(event)?;
+    writeln!(&stream, "ok")?;
+    Ok(())
+}
+
+/// Sends a command to the running wedder
+pub fn send(command: &str) -> crate::Result<()> {
+    let path = path()?;
+    let mut stream = UnixStream::connect(path).map_err(|_| "wedder isn't running")?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    writeln!(stream, "{}", command)?;
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply)?;
+    match reply.trim() {
+        "ok" => Ok(()),
+        reply => Err(reply.to_string().into()),
+    }
+}
+
+pub fn remove() {
+    if !LISTENING.load(Ordering::SeqCst) {
+        return;
+    }
+    if let Ok(path) = path() {
+        if let Err(err) = fs::remove_file(&path) {
+            warn!("Couldn't remove socket {:?}: {}", path, err);
+        }
+    }
+}
+
+fn path() -> crate::Result<PathBuf> {
+    runtime::path(&format!("{}.sock", APP_NAME)).ok_or_else(|| "Couldn't get socket path".into())
+}
+
+#[test]
+fn socket() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    listen(sender.clone()).unwrap();
+    assert!(listen(sender).is_err());
+
+    send("next-format").unwrap();
+    assert_eq!(receiver.recv_timeout(TIMEOUT), Ok(Event::NextFormat));
+    assert!(send("unknown").is_err());
+
+    remove();
+    assert!(send("refresh").is_err());
+}
@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::Deserialize;
+
+use crate::adapters::command;
+use crate::model::air_quality::Pollutants;
+use crate::model::comfort;
+use crate::model::config::CommandConfig;
+use crate::model::language::Language;
+use crate::model::location::Location;
+use crate::model::weather::{
+    Aqi, CurrentWeather, Degree, Hpa, Kelvin, Meter, Millimeter, Ms, Percentage, Uvi, Weather,
+    WeatherCondition,
+};
+
+/// Runs an external program printing the weather as JSON
+///
+/// The location, language and used tags are passed in the environment variables
+/// `WEDDER_LAT`, `WEDDER_LON`, `WEDDER_CITY`, `WEDDER_LANGUAGE` and `WEDDER_TAGS`.
+pub struct Command {
+    config: CommandConfig,
+    language: Language,
+}
+
+impl Command {
+    pub fn new(config: &CommandConfig, language: &Language) -> Self {
+        Self {
+            config: config.clone(),
+            language: language.clone(),
+        }
+    }
+}
+
+impl CurrentWeather for Command {
+    fn weather(
+        &self,
+        location: &Location,
+        tags: &HashSet<String>,
+    ) -> crate::Result<Box<dyn Weather>> {
+        if self.config.command.is_empty() {
+            return Err("No weather command".into());
+        }
+        let mut tags: Vec<_> = tags.iter().map(String::as_str).collect();
+        tags.sort_unstable();
+        let env = [
+            ("WEDDER_LAT", location.lat.to_string()),
+            ("WEDDER_LON", location.lon.to_string()),
+            ("WEDDER_CITY", location.city.clone().unwrap_or_default()),
+            ("WEDDER_LANGUAGE", self.language.to_string()),
+            ("WEDDER_TAGS", tags.join(",")),
+        ];
+        let timeout = Duration::from_secs(self.config.timeout.0);
+        let output = command::run_with_env(&self.config.command, &env, timeout)?;
+        let mut weather: CommandWeather = serde_json::from_str(&output)
+            .map_err(|err| format!("Couldn't parse weather command output: {}", err))?;
+        // the output is live data unless it says otherwise
+        weather.observed_at.get_or_insert(Utc::now().timestamp());
+        Ok(Box::new(weather))
+    }
+}
+
+/// The documented output schema, every field is optional
+///
+/// Temperatures are in °C, times in seconds since the Unix epoch
+/// and the UTC offset in seconds.
+#[derive(Debug, Default, Deserialize)]
+struct CommandWeather {
+    condition: Option<WeatherCondition>,
+    description: Option<String>,
+    temperature: Option<f32>,
+    temperature_feels_like: Option<f32>,
+    temperature_max: Option<f32>,
+    temperature_min: Option<f32>,
+    dew_point: Option<f32>,
+    precipitation: Option<Millimeter>,
+    precipitation_chance: Option<Percentage>,
+    clouds: Option<Percentage>,
+    humidity: Option<Percentage>,
+    visibility: Option<Meter>,
+    wind_speed: Option<Ms>,
+    wind_gust: Option<Ms>,
+    wind_direction: Option<Degree>,
+    pressure: Option<Hpa>,
+    uvi: Option<Uvi>,
+    air_quality_index: Option<Aqi>,
+    pollutants: Option<Pollutants>,
+    sunrise: Option<i64>,
+    sunset: Option<i64>,
+    utc_offset: Option<i32>,
+    observed_at: Option<i64>,
+}
+
+impl Weather for CommandWeather {
+    fn weather_condition(&self) -> Option<WeatherCondition> {
+        self.condition
+    }
+
+    fn description(&self) -> Option<String> {
+        self.description.clone()
+    }
+
+    fn temp(&self) -> Option<Kelvin> {
+        self.temperature.map(Kelvin::from_celsius)
+    }
+
+    fn temp_feels_like(&self) -> Option<Kelvin> {
+        self.temperature_feels_like.map(Kelvin::from_celsius)
+    }
+
+    fn temp_max(&self) -> Option<Kelvin> {
+        self.temperature_max.map(Kelvin::from_celsius)
+    }
+
+    fn temp_min(&self) -> Option<Kelvin> {
+        self.temperature_min.map(Kelvin::from_celsius)
+    }
+
+    fn dew_point(&self) -> Option<Kelvin> {
+        match self.dew_point {
+            Some(dew_point) => Some(Kelvin::from_celsius(dew_point)),
+            None => comfort::dew_point(self.temp()?, self.humidity()?),
+        }
+    }
+
+    fn precipitation(&self) -> Option<Millimeter> {
+        self.precipitation
+    }
+
+    fn precipitation_chance(&self) -> Option<Percentage> {
+        self.precipitation_chance
+    }
+
+    fn clouds(&self) -> Option<Percentage> {
+        self.clouds
+    }
+
+    fn humidity(&self) -> Option<Percentage> {
+        self.humidity
+    }
+
+    fn visibility(&self) -> Option<Meter> {
+        self.visibility
+    }
+
+    fn wind_speed(&self) -> Option<Ms> {
+        self.wind_speed
+    }
+
+    fn wind_gust(&self) -> Option<Ms> {
+        self.wind_gust
+    }
+
+    fn wind_direction(&self) -> Option<Degree> {
+        self.wind_direction
+    }
+
+    fn pressure(&self) -> Option<Hpa> {
+        self.pressure
+    }
+
+    fn uvi(&self) -> Option<Uvi> {
+        self.uvi
+    }
+
+    fn aqi(&self) -> Option<Aqi> {
+        self.air_quality_index
+    }
+
+    fn pollutants(&self) -> Option<Pollutants> {
+        self.pollutants
+    }
+
+    fn sunrise(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.sunrise?, 0)
+    }
+
+    fn sunset(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.sunset?, 0)
+    }
+
+    fn utc_offset(&self) -> Option<FixedOffset> {
+        FixedOffset::east_opt(self.utc_offset?)
+    }
+
+    fn observed_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.observed_at?, 0)
+    }
+}
+
+#[test]
+fn weather() {
+    let location = Location {
+        city: Some("London".to_string()),
+        lat: 51.5,
+        lon: -0.1,
+    };
+    let tags = HashSet::from(["temperature".to_string(), "icon".to_string()]);
+    let json = r#"{"condition": "light_rain", "temperature": '$WEDDER_LAT', "description": "'$WEDDER_TAGS'", "humidity": 80, "sunrise": 1700000000}"#;
+    let config = CommandConfig {
+        command: format!("echo '{}'", json),
+        ..Default::default()
+    };
+    let weather = Command::new(&config, &Language::En)
+        .weather(&location, &tags)
+        .unwrap();
+
+    assert_eq!(
+        weather.weather_condition(),
+        Some(WeatherCondition::LightRain)
+    );
+    assert_eq!(weather.description().unwrap(), "icon,temperature");
+    assert!((weather.temp().unwrap().0 - 324.65).abs() < 0.01);
+    assert_eq!(weather.humidity().unwrap().0, 80.0);
+    assert!(weather.dew_point().is_some());
+    assert!(weather.sunrise().is_some());
+    assert!(weather.sunset().is_none());
+    assert!(weather.observed_at().is_some());
+
+    let config = CommandConfig {
+        command: "echo '{\"temperature\": \"warm\"}'".to_string(),
+        ..Default::default()
+    };
+    let weather = Command::new(&config, &Language::En).weather(&location, &tags);
+    assert!(weather.is_err());
+}
@@ -0,0 +1,168 @@
+use chrono::Utc;
+use serde::Deserialize;
+
+use crate::adapters::http::Client;
+use crate::adapters::weather::station::Observation;
+use crate::model::config::EcowittConfig;
+use crate::model::location::Location;
+use crate::model::weather::{
+    CurrentWeather, Degree, Hpa, Kelvin, Millimeter, Ms, Percentage, Uvi, Weather,
+};
+
+/// Live data of an Ecowitt gateway queried over the local network
+pub struct Ecowitt {
+    config: EcowittConfig,
+    client: &'static dyn Client,
+}
+
+impl Ecowitt {
+    pub fn new(config: &EcowittConfig, client: &'static dyn Client) -> Self {
+        Self {
+            config: config.clone(),
+            client,
+        }
+    }
+
+    /// The gateway reports values in the units configured on it, e.g. "4.5 mph" or "81%"
+    fn observation(response: Response) -> crate::Result<Box<dyn Weather>> {
+        let common = |id: &str| response.common_list.iter().find(|value| value.id == id);
+        let temperature = |id| common(id).and_then(LiveValue::temperature);
+        let speed = |id| common(id).and_then(LiveValue::speed);
+        let number = |id| common(id).and_then(LiveValue::number);
+        let rain_rate = response
+            .rain
+            .iter()
+            .find(|value| value.id == "0x0E")
+            .and_then(LiveValue::rain);
+        let pressure = response
+            .wh25
+            .first()
+            .and_then(|wh25| wh25.rel.as_deref())
+            .and_then(pressure);
+
+        Ok(Box::new(Observation {
+            temp: temperature("0x02"),
+            temp_feels_like: temperature("3"),
+            dew_point: temperature("0x03"),
+            precipitation: rain_rate,
+            humidity: number("0x07").map(Percentage),
+            wind_speed: speed("0x0B"),
+            wind_gust: speed("0x0C"),
+            wind_direction: number("0x0A").map(Degree),
+            pressure,
+            uvi: number("0x17").map(Uvi),
+            // the gateway reports live data
+            observed_at: Some(Utc::now()),
+            ..Default::default()
+        }))
+    }
+}
+
+impl CurrentWeather for Ecowitt {
+    fn weather(
+        &self,
+        _location: &Location,
+        _tags: &std::collections::HashSet<String>,
+    ) -> crate::Result<Box<dyn Weather>> {
+        let url = format!("http://{}/get_livedata_info", self.config.host);
+        Self::observation(self.client.get(&url)?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    #[serde(default)]
+    common_list: Vec<LiveValue>,
+    #[serde(default)]
+    rain: Vec<LiveValue>,
+    #[serde(default)]
+    wh25: Vec<Wh25>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LiveValue {
+    id: String,
+    val: String,
+    unit: Option<String>,
+}
+
+/// The indoor sensor of the gateway, which also measures the pressure
+#[derive(Debug, Deserialize)]
+struct Wh25 {
+    rel: Option<String>,
+}
+
+impl LiveValue {
+    /// Splits the value from a unit given inline
+    fn split(&self) -> Option<(f32, &str)> {
+        let val = self.val.trim();
+        let end = val
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+            .unwrap_or(val.len());
+        let number = val[..end].parse().ok()?;
+        Some((number, val[end..].trim()))
+    }
+
+    fn number(&self) -> Option<f32> {
+        self.split().map(|(number, _)| number)
+    }
+
+    fn temperature(&self) -> Option<Kelvin> {
+        let number = self.number()?;
+        match self.unit.as_deref() {
+            Some("F") => Some(Kelvin::from_fahrenheit(number)),
+            _ => Some(Kelvin::from_celsius(number)),
+        }
+    }
+
+    fn speed(&self) -> Option<Ms> {
+        let (number, unit) = self.split()?;
+        match unit {
+            "km/h" => Some(Ms::from_kmh(number)),
+            "m/s" => Some(Ms(number)),
+            "mph" => Some(Ms(number * 0.44704)),
+            "knots" => Some(Ms(number * 0.514444)),
+            _ => None,
+        }
+    }
+
+    /// Rain rate per hour
+    fn rain(&self) -> Option<Millimeter> {
+        let (number, unit) = self.split()?;
+        match unit.trim_end_matches("/Hr") {
+            "mm" => Some(Millimeter(number)),
+            "in" => Some(Millimeter(number * 25.4)),
+            _ => None,
+        }
+    }
+}
+
+fn pressure(value: &str) -> Option<Hpa> {
+    let (number, unit) = value.trim().split_once(' ')?;
+    let number: f32 = number.parse().ok()?;
+    match unit.trim() {
+        "hPa" => Some(Hpa(number)),
+        "inHg" => Some(Hpa(number * 33.8639)),
+        "mmHg" => Some(Hpa(number * 1.33322)),
+        _ => None,
+    }
+}
+
+#[test]
+fn weather() {
+    use crate::adapters::fixtures::Fixtures;
+
+    let location = Location::default();
+    let weather = Ecowitt::new(&EcowittConfig::default(), &Fixtures)
+        .weather(&location, &Default::default())
+        .unwrap();
+
+    assert!((weather.temp().unwrap().0 - 285.15).abs() < 0.01);
+    assert!((weather.dew_point().unwrap().0 - 282.04).abs() < 0.01);
+    assert_eq!(weather.humidity().unwrap().0, 81.0);
+    assert!((weather.wind_speed().unwrap().0 - 2.012).abs() < 0.001);
+    assert_eq!(weather.wind_direction().unwrap().0, 212.0);
+    assert!((weather.precipitation().unwrap().0 - 0.508).abs() < 0.001);
+    assert!((weather.pressure().unwrap().0 - 1016.93).abs() < 0.01);
+    assert_eq!(weather.uvi().unwrap().0, 1.0);
+}
@@ -1 +1,6 @@
+pub mod command;
+pub mod ecowitt;
+pub mod netatmo;
 pub mod owm;
+mod station;
+pub mod weather_underground;
@@ -0,0 +1,292 @@
+use std::sync::Mutex;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::adapters::cache;
+use crate::adapters::http::{self, Client};
+use crate::adapters::weather::station::Observation;
+use crate::model::config::NetatmoConfig;
+use crate::model::location::Location;
+use crate::model::weather::{
+    CurrentWeather, Degree, Freshness, Hpa, Kelvin, Millimeter, Ms, Percentage, Weather,
+};
+
+const CACHE_NAME: &str = "netatmo";
+
+/// Current measurements of a Netatmo weather station and its modules
+pub struct Netatmo {
+    config: NetatmoConfig,
+    client: &'static dyn Client,
+    access_token: Mutex<Option<AccessToken>>,
+}
+
+struct AccessToken {
+    token: String,
+    /// Wall clock time, which keeps running while the machine is suspended
+    expires: DateTime<Utc>,
+}
+
+/// Netatmo rotates refresh tokens, so the latest one has to outlive restarts
+#[derive(Serialize, Deserialize)]
+struct CachedToken {
+    /// The refresh token from the config this one was rotated from
+    configured: String,
+    refresh_token: String,
+}
+
+impl Netatmo {
+    const TOKEN_URL: &'static str = "https://api.netatmo.com/oauth2/token";
+    const URL: &'static str = "https://api.netatmo.com/api/getstationsdata?get_favorites=false";
+
+    pub fn new(config: &NetatmoConfig, client: &'static dyn Client) -> Self {
+        Self {
+            config: config.clone(),
+            client,
+            access_token: Mutex::new(None),
+        }
+    }
+
+    fn access_token(&self) -> crate::Result<String> {
+        let mut access_token = self
+            .access_token
+            .lock()
+            .map_err(|_| "Netatmo token lock poisoned")?;
+        if let Some(token) = access_token
+            .as_ref()
+            .filter(|token| token.expires > Utc::now())
+        {
+            return Ok(token.token.clone());
+        }
+
+        debug!("Refreshing Netatmo access token...");
+        let refresh_token = cache::load(CACHE_NAME)
+            .filter(|cached: &CachedToken| cached.configured == self.config.refresh_token)
+            .map(|cached| cached.refresh_token)
+            .unwrap_or_else(|| self.config.refresh_token.clone());
+        let response: TokenResponse = self.client.post_form(
+            Self::TOKEN_URL,
+            &[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", &refresh_token),
+                ("client_id", &self.config.client_id),
+                ("client_secret", &self.config.client_secret),
+            ],
+        )?;
+        let (Some(token), Some(refresh_token)) = (response.access_token, response.refresh_token)
+        else {
+            let error = response.error.unwrap_or_default();
+            return Err(format!("Couldn't refresh Netatmo access token: {}", error).into());
+        };
+
+        let cached = CachedToken {
+            configured: self.config.refresh_token.clone(),
+            refresh_token,
+        };
+        if let Err(err) = cache::save(CACHE_NAME, &cached) {
+            warn!("Couldn't cache Netatmo refresh token: {}", err);
+        }
+        // refresh a minute early to not use an expired token
+        let expires_in = response.expires_in.unwrap_or_default().saturating_sub(60);
+        *access_token = Some(AccessToken {
+            token: token.clone(),
+            expires: Utc::now() + TimeDelta::seconds(expires_in as i64),
+        });
+        Ok(token)
+    }
+
+    /// Makes the next access refresh the token, e.g. after it was revoked
+    fn forget_access_token(&self) -> crate::Result<()> {
+        let mut access_token = self
+            .access_token
+            .lock()
+            .map_err(|_| "Netatmo token lock poisoned")?;
+        *access_token = None;
+        Ok(())
+    }
+
+    fn observation(
+        &self,
+        response: Response,
+        freshness: Freshness,
+    ) -> crate::Result<Box<dyn Weather>> {
+        let device = response
+            .body
+            .devices
+            .into_iter()
+            .find(|device| self.config.device_id.is_empty() || device.id == self.config.device_id)
+            .ok_or("Netatmo station not found")?;
+        let module = |module_type: &str| {
+            device
+                .modules
+                .iter()
+                .find(|module| module.module_type == module_type)
+                .and_then(|module| module.dashboard_data.as_ref())
+        };
+        let outdoor = module("NAModule1");
+        let wind = module("NAModule2");
+        let rain = module("NAModule3");
+
+        Ok(Box::new(Observation {
+            temp: outdoor
+                .and_then(|data| data.temperature)
+                .map(Kelvin::from_celsius),
+            humidity: outdoor.and_then(|data| data.humidity).map(Percentage),
+            pressure: device
+                .dashboard_data
+                .as_ref()
+                .and_then(|data| data.pressure)
+                .map(Hpa),
+            wind_speed: wind.and_then(|data| data.wind_strength).map(Ms::from_kmh),
+            wind_gust: wind.and_then(|data| data.gust_strength).map(Ms::from_kmh),
+            wind_direction: wind.and_then(|data| data.wind_angle).map(Degree),
+            precipitation: rain.and_then(|data| data.sum_rain_1).map(Millimeter),
+            observed_at: outdoor
+                .or(device.dashboard_data.as_ref())
+                .and_then(|data| data.time_utc)
+                .and_then(|time| DateTime::from_timestamp(time, 0)),
+            freshness,
+            ..Default::default()
+        }))
+    }
+}
+
+impl CurrentWeather for Netatmo {
+    fn weather(
+        &self,
+        _location: &Location,
+        _tags: &std::collections::HashSet<String>,
+    ) -> crate::Result<Box<dyn Weather>> {
+        let token = self.access_token()?;
+        let url = match self.config.device_id.as_str() {
+            "" => Self::URL.to_string(),
+            device_id => format!("{}&device_id={}", Self::URL, device_id),
+        };
+        let (response, freshness) = match self.client.get_authorized(&url, &token) {
+            Err(err) if token_rejected(err.as_ref()) => {
+                debug!("Netatmo access token rejected");
+                self.forget_access_token()?;
+                let token = self.access_token()?;
+                self.client.get_authorized(&url, &token)?
+            }
+            response => response?,
+        };
+        self.observation(response, freshness)
+    }
+}
+
+/// Netatmo rejects invalid and expired access tokens with HTTP 403 and error codes 2 and 3
+fn token_rejected(err: &(dyn std::error::Error + 'static)) -> bool {
+    if err.is::<http::Unauthorized>() {
+        return true;
+    }
+    err.downcast_ref::<http::Status>()
+        .filter(|status| status.code == 403)
+        .and_then(|status| serde_json::from_str::<ErrorResponse>(&status.body).ok())
+        .is_some_and(|response| matches!(response.error.code, 2 | 3))
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    error: ApiError,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    code: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    body: Body,
+}
+
+#[derive(Debug, Deserialize)]
+struct Body {
+    devices: Vec<Device>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Device {
+    #[serde(rename = "_id")]
+    id: String,
+    dashboard_data: Option<DashboardData>,
+    #[serde(default)]
+    modules: Vec<Module>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Module {
+    #[serde(rename = "type")]
+    module_type: String,
+    dashboard_data: Option<DashboardData>,
+}
+
+/// In °C, %, mbar, km/h and mm
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DashboardData {
+    temperature: Option<f32>,
+    humidity: Option<f32>,
+    pressure: Option<f32>,
+    wind_strength: Option<f32>,
+    wind_angle: Option<f32>,
+    gust_strength: Option<f32>,
+    #[serde(rename = "sum_rain_1")]
+    sum_rain_1: Option<f32>,
+    /// Time of the measurement in seconds since the Unix epoch
+    #[serde(rename = "time_utc")]
+    time_utc: Option<i64>,
+}
+
+#[test]
+fn weather() {
+    use crate::adapters::fixtures::Fixtures;
+
+    let location = Location::default();
+    let weather = Netatmo::new(&NetatmoConfig::default(), &Fixtures)
+        .weather(&location, &Default::default())
+        .unwrap();
+
+    assert!((weather.temp().unwrap().0 - 277.35).abs() < 0.01);
+    assert_eq!(weather.humidity().unwrap().0, 88.0);
+    assert_eq!(weather.pressure().unwrap().0, 997.6);
+    assert_eq!(weather.wind_speed().unwrap().0, 5.0);
+    assert_eq!(weather.wind_direction().unwrap().0, 270.0);
+    assert_eq!(weather.precipitation().unwrap().0, 0.4);
+    assert_eq!(weather.observed_at().unwrap().timestamp(), 1555677739);
+    // computed wind chill
+    assert!(weather.temp_feels_like().unwrap().0 < weather.temp().unwrap().0);
+
+    let unknown = NetatmoConfig {
+        device_id: "unknown".to_string(),
+        ..Default::default()
+    };
+    let weather = Netatmo::new(&unknown, &Fixtures).weather(&location, &Default::default());
+    assert!(weather.is_err());
+}
+
+#[test]
+fn rejected_token() {
+    let status = |code, body: &str| http::Status {
+        code,
+        body: body.to_string(),
+    };
+    assert!(token_rejected(&http::Unauthorized));
+    let expired = r#"{"error":{"code":3,"message":"Access token expired"}}"#;
+    assert!(token_rejected(&status(403, expired)));
+    let invalid = r#"{"error":{"code":2,"message":"Invalid access token"}}"#;
+    assert!(token_rejected(&status(403, invalid)));
+    let forbidden = r#"{"error":{"code":13,"message":"Operation forbidden"}}"#;
+    assert!(!token_rejected(&status(403, forbidden)));
+    assert!(!token_rejected(&status(500, expired)));
+}
@@ -1,43 +1,141 @@
-use crate::adapters::http;
+use crate::adapters::http::Client;
+use crate::model::air_quality::Pollutants;
+use crate::model::config::OpenWeatherMapConfig;
+use crate::model::language::Language;
 use crate::model::location::Location;
 use crate::model::weather::{
-    Aqi, CurrentWeather, Hpa, Kelvin, Meter, Millimeter, Ms, Percentage, Uvi, Weather,
-    WeatherCondition,
+    Aqi, CurrentWeather, Degree, Freshness, Hpa, Kelvin, Meter, Millimeter, Ms, Percentage, Uvi,
+    Weather, WeatherCondition,
 };
-use chrono::{DateTime, Local};
+use chrono::{DateTime, FixedOffset, Utc};
 use serde::Deserialize;
+use std::collections::HashSet;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
+use std::time::Duration;
 
-pub struct OpenWeatherMap;
+pub struct OpenWeatherMap {
+    config: OpenWeatherMapConfig,
+    client: &'static dyn Client,
+    language: Language,
+}
 
 impl OpenWeatherMap {
-    pub fn new() -> Self {
-        Self
+    pub fn new(
+        config: &OpenWeatherMapConfig,
+        language: &Language,
+        client: &'static dyn Client,
+    ) -> Self {
+        Self {
+            config: config.clone(),
+            client,
+            language: language.clone(),
+        }
     }
+
+    fn url(&self, api: &str) -> String {
+        format!(
+            "{}/data/{}/{}?",
+            self.config.endpoint.trim_end_matches('/'),
+            self.config.api_version,
+            api
+        )
+    }
+
+    fn one_call_url(&self) -> String {
+        format!(
+            "{}/data/{}/{}?",
+            self.config.endpoint.trim_end_matches('/'),
+            OneCall::VERSION,
+            OneCall::API
+        )
+    }
+}
+
+/// Tags requiring the air pollution endpoint
+const AIR_POLLUTION_TAGS: [&str; 9] = [
+    "air_quality_index",
+    "air_quality",
+    "pm2_5",
+    "pm10",
+    "o3",
+    "no2",
+    "so2",
+    "co",
+    "nh3",
+];
+
+/// Current weather data is recalculated every 10 minutes
+const UPDATE_PERIOD: Duration = Duration::from_secs(600);
+
+fn uses_air_pollution(tags: &HashSet<String>) -> bool {
+    AIR_POLLUTION_TAGS.iter().any(|tag| tags.contains(*tag))
 }
 
-#[cfg(not(feature = "test"))]
 impl CurrentWeather for OpenWeatherMap {
-    fn weather(&self, location: &Location, api_key: &str) -> crate::Result<Box<dyn Weather>> {
+    fn weather(
+        &self,
+        location: &Location,
+        tags: &HashSet<String>,
+    ) -> crate::Result<Box<dyn Weather>> {
         let current_weather_url = format!(
-            "{}lat={}&lon={}&APPID={}",
-            Current::URL,
+            "{}lat={}&lon={}&lang={}&APPID={}",
+            self.url(Current::API),
             location.lat,
             location.lon,
-            api_key
+            self.language,
+            self.config.api_key
         );
         let air_pollution_url = format!(
             "{}lat={}&lon={}&APPID={}",
-            AirPollution::URL,
+            self.url(AirPollution::API),
+            location.lat,
+            location.lon,
+            self.config.api_key
+        );
+
+        let one_call_url = format!(
+            "{}lat={}&lon={}&exclude=current,minutely,hourly,daily&APPID={}",
+            self.one_call_url(),
             location.lat,
             location.lon,
-            api_key
+            self.config.api_key
         );
 
-        let current = http::get(&current_weather_url).ok();
-        let air_pollution = http::get(&air_pollution_url).ok();
-        Ok(Box::new(OwmWeather::new(current, air_pollution)))
+        // without the current weather there is nothing to show, air pollution and alerts are
+        // optional
+        let (current, mut freshness) = self.client.get_fresh(&current_weather_url)?;
+        let air_pollution = if uses_air_pollution(tags) {
+            self.client.get_fresh(&air_pollution_url).ok().map(
+                |(air_pollution, air_pollution_freshness)| {
+                    freshness = freshness.merge(air_pollution_freshness);
+                    air_pollution
+                },
+            )
+        } else {
+            None
+        };
+        let one_call = if self.config.alerts {
+            self.client
+                .get_fresh(&one_call_url)
+                .ok()
+                .map(|(one_call, one_call_freshness)| {
+                    freshness = freshness.merge(one_call_freshness);
+                    one_call
+                })
+        } else {
+            None
+        };
+        Ok(Box::new(OwmWeather::new(
+            Some(current),
+            air_pollution,
+            one_call,
+            freshness,
+        )))
+    }
+
+    fn update_period(&self) -> Option<Duration> {
+        Some(UPDATE_PERIOD)
     }
 }
 
@@ -45,13 +143,22 @@ impl CurrentWeather for OpenWeatherMap {
 struct OwmWeather {
     current: Option<Current>,
     air_pollution: Option<AirPollution>,
+    one_call: Option<OneCall>,
+    freshness: Freshness,
 }
 
 impl OwmWeather {
-    fn new(current: Option<Current>, air_pollution: Option<AirPollution>) -> Self {
+    fn new(
+        current: Option<Current>,
+        air_pollution: Option<AirPollution>,
+        one_call: Option<OneCall>,
+        freshness: Freshness,
+    ) -> Self {
         Self {
             current,
             air_pollution,
+            one_call,
+            freshness,
         }
     }
 }
@@ -59,19 +166,16 @@ impl OwmWeather {
 impl Weather for OwmWeather {
     fn weather_condition(&self) -> Option<WeatherCondition> {
         let conditions = self.current.as_ref()?.weather.as_ref()?;
-        let icon = &*conditions.first()?.icon;
-        match icon {
-            "01d" | "01n" => Some(WeatherCondition::ClearSky),
-            "02d" | "02n" => Some(WeatherCondition::FewClouds),
-            "03d" | "03n" => Some(WeatherCondition::Clouds),
-            "04d" | "04n" => Some(WeatherCondition::ManyClouds),
-            "09d" | "09n" => Some(WeatherCondition::Rain),
-            "10d" | "10n" => Some(WeatherCondition::HeavyRain),
-            "11d" | "11n" => Some(WeatherCondition::Thunderstorm),
-            "13d" | "13n" => Some(WeatherCondition::Snow),
-            "50d" | "50n" => Some(WeatherCondition::Mist),
-            _ => None,
-        }
+        let condition = conditions.first()?;
+        condition
+            .id
+            .and_then(condition_from_id)
+            .or_else(|| condition_from_icon(&condition.icon))
+    }
+
+    fn description(&self) -> Option<String> {
+        let conditions = self.current.as_ref()?.weather.as_ref()?;
+        conditions.first()?.description.clone()
     }
 
     fn temp(&self) -> Option<Kelvin> {
@@ -90,10 +194,6 @@ impl Weather for OwmWeather {
         self.current.as_ref()?.main.as_ref()?.temp_min
     }
 
-    fn dew_point(&self) -> Option<Kelvin> {
-        None
-    }
-
     fn precipitation(&self) -> Option<Millimeter> {
         let rain = self
             .current
@@ -138,6 +238,14 @@ impl Weather for OwmWeather {
         self.current.as_ref()?.wind.as_ref()?.speed
     }
 
+    fn wind_gust(&self) -> Option<Ms> {
+        self.current.as_ref()?.wind.as_ref()?.gust
+    }
+
+    fn wind_direction(&self) -> Option<Degree> {
+        self.current.as_ref()?.wind.as_ref()?.deg
+    }
+
     fn pressure(&self) -> Option<Hpa> {
         self.current.as_ref()?.main.as_ref()?.pressure
     }
@@ -156,7 +264,11 @@ impl Weather for OwmWeather {
             .aqi
     }
 
-    fn sunrise(&self) -> Option<DateTime<Local>> {
+    fn pollutants(&self) -> Option<Pollutants> {
+        self.air_pollution.as_ref()?.list.first()?.components
+    }
+
+    fn sunrise(&self) -> Option<DateTime<Utc>> {
         self.current
             .as_ref()?
             .sys
@@ -165,9 +277,40 @@ impl Weather for OwmWeather {
             .map(to_datetime)
     }
 
-    fn sunset(&self) -> Option<DateTime<Local>> {
+    fn sunset(&self) -> Option<DateTime<Utc>> {
         self.current.as_ref()?.sys.as_ref()?.sunset.map(to_datetime)
     }
+
+    fn utc_offset(&self) -> Option<FixedOffset> {
+        FixedOffset::east_opt(self.current.as_ref()?.timezone?)
+    }
+
+    fn observed_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.current.as_ref()?.dt?, 0)
+    }
+
+    fn freshness(&self) -> Freshness {
+        self.freshness
+    }
+
+    fn has_alert(&self) -> bool {
+        let now = Utc::now().timestamp();
+        self.one_call.as_ref().is_some_and(|one_call| {
+            one_call
+                .alerts
+                .iter()
+                .any(|alert| (alert.start..alert.end).contains(&now))
+        })
+    }
+
+    fn is_day(&self) -> Option<bool> {
+        let conditions = self.current.as_ref()?.weather.as_ref()?;
+        match conditions.first()?.icon.chars().last()? {
+            'd' => Some(true),
+            'n' => Some(false),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -180,10 +323,14 @@ struct Current {
     snow: Option<Snow>,
     clouds: Option<Clouds>,
     sys: Option<Sys>,
+    /// Shift in seconds from UTC
+    timezone: Option<i32>,
+    /// Time of the calculation in seconds since the Unix epoch
+    dt: Option<i64>,
 }
 
 impl Current {
-    const URL: &'static str = "http://api.openweathermap.org/data/2.5/weather?";
+    const API: &'static str = "weather";
 }
 
 #[derive(Debug, Deserialize)]
@@ -199,6 +346,8 @@ struct CurrentMain {
 #[derive(Debug, Deserialize)]
 struct Wind {
     speed: Option<Ms>,
+    deg: Option<Degree>,
+    gust: Option<Ms>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -214,9 +363,61 @@ struct Sys {
 
 #[derive(Debug, Deserialize)]
 struct OwmWeatherCondition {
+    id: Option<u16>,
+    description: Option<String>,
     icon: String,
 }
 
+/// Maps the weather condition codes listed under https://openweathermap.org/weather-conditions
+fn condition_from_id(id: u16) -> Option<WeatherCondition> {
+    let condition = match id {
+        200..=299 => WeatherCondition::Thunderstorm,
+        300..=399 => WeatherCondition::Drizzle,
+        500 => WeatherCondition::LightRain,
+        501 => WeatherCondition::Rain,
+        502..=504 => WeatherCondition::HeavyRain,
+        511 => WeatherCondition::FreezingRain,
+        520..=531 => WeatherCondition::ShowerRain,
+        600 => WeatherCondition::LightSnow,
+        601 => WeatherCondition::Snow,
+        602 => WeatherCondition::HeavySnow,
+        611..=613 => WeatherCondition::Sleet,
+        615 | 616 => WeatherCondition::RainAndSnow,
+        620..=622 => WeatherCondition::ShowerSnow,
+        701 => WeatherCondition::Mist,
+        711 => WeatherCondition::Smoke,
+        721 => WeatherCondition::Haze,
+        731 | 761 => WeatherCondition::Dust,
+        741 => WeatherCondition::Fog,
+        751 => WeatherCondition::Sand,
+        762 => WeatherCondition::VolcanicAsh,
+        771 => WeatherCondition::Squalls,
+        781 => WeatherCondition::Tornado,
+        800 => WeatherCondition::ClearSky,
+        801 => WeatherCondition::FewClouds,
+        802 => WeatherCondition::Clouds,
+        803 => WeatherCondition::ManyClouds,
+        804 => WeatherCondition::Overcast,
+        _ => return None,
+    };
+    Some(condition)
+}
+
+fn condition_from_icon(icon: &str) -> Option<WeatherCondition> {
+    match icon {
+        "01d" | "01n" => Some(WeatherCondition::ClearSky),
+        "02d" | "02n" => Some(WeatherCondition::FewClouds),
+        "03d" | "03n" => Some(WeatherCondition::Clouds),
+        "04d" | "04n" => Some(WeatherCondition::ManyClouds),
+        "09d" | "09n" => Some(WeatherCondition::Rain),
+        "10d" | "10n" => Some(WeatherCondition::HeavyRain),
+        "11d" | "11n" => Some(WeatherCondition::Thunderstorm),
+        "13d" | "13n" => Some(WeatherCondition::Snow),
+        "50d" | "50n" => Some(WeatherCondition::Mist),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct Rain {
     #[serde(rename = "1h")]
@@ -235,7 +436,7 @@ struct AirPollution {
 }
 
 impl AirPollution {
-    const URL: &'static str = "http://api.openweathermap.org/data/2.5/air_pollution?";
+    const API: &'static str = "air_pollution";
 }
 
 impl Debug for AirPollution {
@@ -249,6 +450,7 @@ impl Debug for AirPollution {
 #[derive(Debug, Deserialize)]
 struct ListItem {
     main: Option<AirPollutionMain>,
+    components: Option<Pollutants>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -256,56 +458,131 @@ struct AirPollutionMain {
     aqi: Option<Aqi>,
 }
 
-fn to_datetime(unix_timestamp: i64) -> DateTime<Local> {
-    let time = DateTime::from_timestamp(unix_timestamp, 0).unwrap();
-    time.with_timezone(&Local)
+/// Only the alerts are requested, the rest is excluded
+#[derive(Debug, Deserialize)]
+struct OneCall {
+    #[serde(default)]
+    alerts: Vec<Alert>,
 }
 
-#[cfg(feature = "test")]
-impl CurrentWeather for OpenWeatherMap {
-    fn weather(&self, location: &Location, api_key: &str) -> crate::Result<Box<dyn Weather>> {
-        let current_str = include_str!("../../../tests/current.json");
-        let air_pollution_str = include_str!("../../../tests/air_pollution.json");
-        let current: Option<Current> = serde_json::from_str(current_str).ok();
-        let air_pollution: Option<AirPollution> = serde_json::from_str(air_pollution_str).ok();
-        Ok(Box::new(OwmWeather::new(current, air_pollution)))
-    }
+impl OneCall {
+    const API: &'static str = "onecall";
+    const VERSION: &'static str = "3.0";
+}
+
+#[derive(Debug, Deserialize)]
+struct Alert {
+    /// Times in seconds since the Unix epoch
+    start: i64,
+    end: i64,
+}
+
+fn to_datetime(unix_timestamp: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(unix_timestamp, 0).unwrap()
 }
 
 #[test]
 fn weather() {
+    use crate::adapters::fixtures::Fixtures;
+
     let location = Location {
         city: None,
         lat: 0.0,
         lon: 0.0,
     };
-    let weather = OpenWeatherMap::new().weather(&location, "");
+    let tags = HashSet::from(["air_quality_index".to_string()]);
+    let weather = OpenWeatherMap::new(&OpenWeatherMapConfig::default(), &Language::En, &Fixtures)
+        .weather(&location, &tags);
     assert!(weather.is_ok());
     let weather = weather.unwrap();
 
     assert_eq!(weather.weather_condition().unwrap(), WeatherCondition::Mist);
+    assert_eq!(weather.description().unwrap(), "mist");
+    assert_eq!(weather.is_day(), Some(false));
     assert_eq!(weather.temp().unwrap().0, 274.753);
     assert_eq!(weather.temp_feels_like().unwrap().0, 270.456);
     assert_eq!(weather.temp_max().unwrap().0, 279.471);
     assert_eq!(weather.temp_min().unwrap().0, 273.159);
-    assert_eq!(weather.dew_point(), None);
+    assert!((weather.dew_point().unwrap().0 - 274.27).abs() < 0.1);
     assert_eq!(weather.precipitation().unwrap().0, 0.074);
     assert_eq!(weather.precipitation_chance(), None);
     assert_eq!(weather.clouds().unwrap().0, 90.563);
     assert_eq!(weather.humidity().unwrap().0, 96.775);
     assert_eq!(weather.visibility().unwrap().0, 6437.888);
     assert_eq!(weather.wind_speed().unwrap().0, 3.654);
+    assert_eq!(weather.wind_gust().unwrap().0, 3.47);
+    assert_eq!(weather.wind_direction().unwrap().0, 121.0);
     assert_eq!(weather.pressure().unwrap().0, 1017.567);
     assert_eq!(weather.uvi(), None);
     assert_eq!(weather.aqi().unwrap().0, 1.33);
+    let pollutants = weather.pollutants().unwrap();
+    assert_eq!(pollutants.pm2_5.unwrap().0, 0.5);
+    assert_eq!(pollutants.o3.unwrap().0, 68.66455);
+    assert_eq!(pollutants.co.unwrap().0, 201.94054);
     assert!(weather.sunrise().is_some());
-    let sunrise = DateTime::<chrono::Utc>::from(weather.sunrise().unwrap())
-        .format("%H:%M")
-        .to_string();
+    let sunrise = weather.sunrise().unwrap().format("%H:%M").to_string();
     assert_eq!(sunrise, "13:13");
     assert!(weather.sunset().is_some());
-    let sunset = DateTime::<chrono::Utc>::from(weather.sunset().unwrap())
-        .format("%H:%M")
-        .to_string();
+    let sunset = weather.sunset().unwrap().format("%H:%M").to_string();
     assert_eq!(sunset, "23:10");
+    assert_eq!(weather.observed_at().unwrap().timestamp(), 1726660758);
+    assert_eq!(weather.utc_offset().unwrap().local_minus_utc(), 7200);
+}
+
+#[test]
+fn skip_air_pollution() {
+    use crate::adapters::fixtures::Fixtures;
+
+    let location = Location {
+        city: None,
+        lat: 0.0,
+        lon: 0.0,
+    };
+    let tags = HashSet::from(["temperature".to_string()]);
+    let weather = OpenWeatherMap::new(&OpenWeatherMapConfig::default(), &Language::En, &Fixtures)
+        .weather(&location, &tags)
+        .unwrap();
+
+    assert!(weather.temp().is_some());
+    assert!(weather.aqi().is_none());
+    assert!(weather.pollutants().is_none());
+}
+
+#[test]
+fn conditions() {
+    assert_eq!(condition_from_id(311), Some(WeatherCondition::Drizzle));
+    assert_eq!(condition_from_id(511), Some(WeatherCondition::FreezingRain));
+    assert_eq!(condition_from_id(612), Some(WeatherCondition::Sleet));
+    assert_eq!(condition_from_id(751), Some(WeatherCondition::Sand));
+    assert_eq!(condition_from_id(781), Some(WeatherCondition::Tornado));
+    assert_eq!(condition_from_id(804), Some(WeatherCondition::Overcast));
+    assert_eq!(condition_from_id(900), None);
+    assert_eq!(
+        condition_from_icon("10n"),
+        Some(WeatherCondition::HeavyRain)
+    );
+}
+
+#[test]
+fn alerts() {
+    use crate::adapters::fixtures::Fixtures;
+
+    let location = Location {
+        city: None,
+        lat: 0.0,
+        lon: 0.0,
+    };
+    let config = OpenWeatherMapConfig {
+        alerts: true,
+        ..Default::default()
+    };
+    let weather = OpenWeatherMap::new(&config, &Language::En, &Fixtures)
+        .weather(&location, &HashSet::new())
+        .unwrap();
+    assert!(weather.has_alert());
+
+    let weather = OpenWeatherMap::new(&OpenWeatherMapConfig::default(), &Language::En, &Fixtures)
+        .weather(&location, &HashSet::new())
+        .unwrap();
+    assert!(!weather.has_alert());
 }
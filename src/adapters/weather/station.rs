@@ -0,0 +1,148 @@
+use chrono::{DateTime, FixedOffset, Utc};
+
+use crate::model::air_quality::Pollutants;
+use crate::model::comfort;
+use crate::model::weather::{
+    Aqi, Degree, Freshness, Hpa, Kelvin, Meter, Millimeter, Ms, Percentage, Uvi, Weather,
+    WeatherCondition,
+};
+
+/// Current observation of a personal weather station, which only measures
+/// and doesn't forecast or classify the weather
+#[derive(Debug, Default)]
+pub struct Observation {
+    pub temp: Option<Kelvin>,
+    pub temp_feels_like: Option<Kelvin>,
+    pub dew_point: Option<Kelvin>,
+    pub heat_index: Option<Kelvin>,
+    pub wind_chill: Option<Kelvin>,
+    /// Rain in the last hour or the current rain rate per hour
+    pub precipitation: Option<Millimeter>,
+    pub humidity: Option<Percentage>,
+    pub wind_speed: Option<Ms>,
+    pub wind_gust: Option<Ms>,
+    pub wind_direction: Option<Degree>,
+    pub pressure: Option<Hpa>,
+    pub uvi: Option<Uvi>,
+    pub observed_at: Option<DateTime<Utc>>,
+    pub freshness: Freshness,
+}
+
+impl Weather for Observation {
+    fn weather_condition(&self) -> Option<WeatherCondition> {
+        None
+    }
+
+    fn description(&self) -> Option<String> {
+        None
+    }
+
+    fn temp(&self) -> Option<Kelvin> {
+        self.temp
+    }
+
+    /// Falls back to the wind chill in the cold, the heat index in the heat and the
+    /// temperature in between
+    fn temp_feels_like(&self) -> Option<Kelvin> {
+        self.temp_feels_like
+            .or_else(|| {
+                if self.temp?.0 <= 283.15 {
+                    self.wind_chill()
+                } else {
+                    self.heat_index()
+                }
+            })
+            .or(self.temp)
+    }
+
+    fn temp_max(&self) -> Option<Kelvin> {
+        None
+    }
+
+    fn temp_min(&self) -> Option<Kelvin> {
+        None
+    }
+
+    fn dew_point(&self) -> Option<Kelvin> {
+        self.dew_point
+            .or_else(|| comfort::dew_point(self.temp?, self.humidity?))
+    }
+
+    fn heat_index(&self) -> Option<Kelvin> {
+        self.heat_index
+            .or_else(|| comfort::heat_index(self.temp?, self.humidity?))
+    }
+
+    fn wind_chill(&self) -> Option<Kelvin> {
+        self.wind_chill
+            .or_else(|| comfort::wind_chill(self.temp?, self.wind_speed?))
+    }
+
+    fn precipitation(&self) -> Option<Millimeter> {
+        self.precipitation
+    }
+
+    fn precipitation_chance(&self) -> Option<Percentage> {
+        None
+    }
+
+    fn clouds(&self) -> Option<Percentage> {
+        None
+    }
+
+    fn humidity(&self) -> Option<Percentage> {
+        self.humidity
+    }
+
+    fn visibility(&self) -> Option<Meter> {
+        None
+    }
+
+    fn wind_speed(&self) -> Option<Ms> {
+        self.wind_speed
+    }
+
+    fn wind_gust(&self) -> Option<Ms> {
+        self.wind_gust
+    }
+
+    fn wind_direction(&self) -> Option<Degree> {
+        self.wind_direction
+    }
+
+    fn pressure(&self) -> Option<Hpa> {
+        self.pressure
+    }
+
+    fn uvi(&self) -> Option<Uvi> {
+        self.uvi
+    }
+
+    fn aqi(&self) -> Option<Aqi> {
+        None
+    }
+
+    fn pollutants(&self) -> Option<Pollutants> {
+        None
+    }
+
+    fn sunrise(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+
+    fn sunset(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+
+    fn utc_offset(&self) -> Option<FixedOffset> {
+        None
+    }
+
+    fn observed_at(&self) -> Option<DateTime<Utc>> {
+        self.observed_at
+    }
+
+    fn freshness(&self) -> Freshness {
+        self.freshness
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::DateTime;
+use serde::Deserialize;
+
+use crate::adapters::http::Client;
+use crate::adapters::weather::station::Observation;
+use crate::model::config::WeatherUndergroundConfig;
+use crate::model::location::Location;
+use crate::model::weather::{
+    CurrentWeather, Degree, Freshness, Hpa, Kelvin, Millimeter, Ms, Percentage, Uvi, Weather,
+};
+
+/// Current observations of a personal weather station on Weather Underground
+pub struct WeatherUnderground {
+    config: WeatherUndergroundConfig,
+    client: &'static dyn Client,
+}
+
+impl WeatherUnderground {
+    const URL: &'static str =
+        "https://api.weather.com/v2/pws/observations/current?format=json&units=m&numericPrecision=decimal";
+
+    pub fn new(config: &WeatherUndergroundConfig, client: &'static dyn Client) -> Self {
+        Self {
+            config: config.clone(),
+            client,
+        }
+    }
+
+    fn observation(response: Response, freshness: Freshness) -> crate::Result<Box<dyn Weather>> {
+        let station = response
+            .observations
+            .into_iter()
+            .next()
+            .ok_or("No observations of the station")?;
+        let metric = station.metric;
+        Ok(Box::new(Observation {
+            temp: metric.temp.map(Kelvin::from_celsius),
+            dew_point: metric.dewpt.map(Kelvin::from_celsius),
+            heat_index: metric.heat_index.map(Kelvin::from_celsius),
+            wind_chill: metric.wind_chill.map(Kelvin::from_celsius),
+            precipitation: metric.precip_rate.map(Millimeter),
+            humidity: station.humidity.map(Percentage),
+            wind_speed: metric.wind_speed.map(Ms::from_kmh),
+            wind_gust: metric.wind_gust.map(Ms::from_kmh),
+            wind_direction: station.winddir.map(Degree),
+            pressure: metric.pressure.map(Hpa),
+            uvi: station.uv.map(Uvi),
+            observed_at: station
+                .epoch
+                .and_then(|epoch| DateTime::from_timestamp(epoch, 0)),
+            freshness,
+            ..Default::default()
+        }))
+    }
+}
+
+impl CurrentWeather for WeatherUnderground {
+    fn weather(
+        &self,
+        _location: &Location,
+        _tags: &std::collections::HashSet<String>,
+    ) -> crate::Result<Box<dyn Weather>> {
+        let url = format!(
+            "{}&stationId={}&apiKey={}",
+            Self::URL,
+            self.config.station_id,
+            self.config.api_key
+        );
+        let (response, freshness) = self.client.get_fresh(&url)?;
+        Self::observation(response, freshness)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    observations: Vec<Station>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Station {
+    humidity: Option<f32>,
+    winddir: Option<f32>,
+    uv: Option<f32>,
+    /// Time of the observation in seconds since the Unix epoch
+    epoch: Option<i64>,
+    metric: Metric,
+}
+
+/// In °C, km/h, hPa and mm
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Metric {
+    temp: Option<f32>,
+    heat_index: Option<f32>,
+    dewpt: Option<f32>,
+    wind_chill: Option<f32>,
+    wind_speed: Option<f32>,
+    wind_gust: Option<f32>,
+    pressure: Option<f32>,
+    precip_rate: Option<f32>,
+}
+
+#[test]
+fn weather() {
+    use crate::adapters::fixtures::Fixtures;
+
+    let location = Location::default();
+    let weather = WeatherUnderground::new(&WeatherUndergroundConfig::default(), &Fixtures)
+        .weather(&location, &Default::default())
+        .unwrap();
+
+    assert!((weather.temp().unwrap().0 - 267.05).abs() < 0.01);
+    assert!((weather.temp_feels_like().unwrap().0 - 262.55).abs() < 0.01);
+    assert!((weather.dew_point().unwrap().0 - 262.75).abs() < 0.01);
+    assert_eq!(weather.humidity().unwrap().0, 71.0);
+    assert!((weather.wind_speed().unwrap().0 - 3.389).abs() < 0.001);
+    assert_eq!(weather.wind_direction().unwrap().0, 329.0);
+    assert_eq!(weather.pressure().unwrap().0, 1019.63);
+    assert_eq!(weather.uvi().unwrap().0, 1.2);
+    assert_eq!(weather.weather_condition(), None);
+    assert_eq!(weather.observed_at().unwrap().timestamp(), 1549291994);
+}
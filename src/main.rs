@@ -1,15 +1,46 @@
+use std::collections::HashSet;
 use std::error::Error;
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, Sender};
 use std::{process, thread, time};
 
+use adapters::clock;
+use adapters::config::cli_args::{CliArgs, Command};
+use adapters::config::watcher;
+use adapters::connectivity;
+use adapters::http::{self, RateLimited};
+use adapters::location;
 use adapters::location::ip_api::IpApi;
 use adapters::location::manual::Manual;
+use adapters::location::network::Network;
+use adapters::pid_file;
+use adapters::pollen::open_meteo::OpenMeteo;
+use adapters::power;
+use adapters::sensor;
+use adapters::session;
+use adapters::signals;
+use adapters::socket;
+use adapters::weather;
+use adapters::weather::ecowitt::Ecowitt;
+use adapters::weather::netatmo::Netatmo;
 use adapters::weather::owm::OpenWeatherMap;
-use log::debug;
-use model::config::{Config, LocationConfig};
+use adapters::weather::weather_underground::WeatherUnderground;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use log::{debug, error, warn};
+use model::config::{Config, LocationConfig, PollenConfig, SensorConfig, WeatherConfig};
+use model::event::Event;
+use model::language::Language;
 use model::location::{CurrentLocation, Location, LocationProvider};
-use model::weather::{CurrentWeather, WeatherProvider};
+use model::pollen::{CurrentPollen, Pollen, PollenProvider, POLLEN_TAGS};
+use model::sensor::{Sensor, SensorProvider, SensorReading, SENSOR_TAGS};
+use model::weather::{CurrentWeather, Freshness, Weather, WeatherProvider};
 use services::config_service;
 use services::format_service::FormatService;
+use services::history_service::HistoryService;
+use services::location_service::LocationService;
+use services::record_service::RecordService;
+use services::schedule_service::{Circumstances, ScheduleService};
+use structopt::StructOpt;
 
 mod adapters;
 mod logger;
@@ -19,6 +50,9 @@ mod services;
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
 const APP_NAME: &str = env!("CARGO_PKG_NAME");
+const RELOAD_DELAY: time::Duration = time::Duration::from_millis(100);
+/// Displayed if there is no weather to show
+const UNAVAILABLE: &str = "Unavailable";
 
 fn main() {
     if let Err(err) = run() {
@@ -27,71 +61,466 @@ fn main() {
     }
 }
 
+#[cfg(not(feature = "test"))]
+fn http_client() -> &'static dyn http::Client {
+    &http::Reqwest
+}
+
+/// Replays the recorded responses of the providers instead of querying them
+#[cfg(feature = "test")]
+fn http_client() -> &'static dyn http::Client {
+    &adapters::fixtures::Fixtures
+}
+
 fn run() -> crate::Result<()> {
-    let config = config_service::config();
-    let app = App::new(config);
-    app.run()
+    let args = CliArgs::from_args();
+    let command = args.command.clone();
+    let config = config_service::config(args.clone());
+    match command {
+        Some(Command::History { from, to }) => history(&config, from, to),
+        // the PID file only reaches a wedder whose socket couldn't be created
+        Some(Command::Refresh) => {
+            socket::send("refresh").or_else(|_| pid_file::signal(libc::SIGUSR1))
+        }
+        Some(Command::NextFormat) => socket::send("next-format"),
+        None => App::new(config, args)?.run(),
+    }
+}
+
+fn history(config: &Config, from: Option<NaiveDate>, to: Option<NaiveDate>) -> crate::Result<()> {
+    let first = from.unwrap_or_else(|| Local::now().date_naive());
+    let last = to.unwrap_or(first);
+    let summary = RecordService::new(config)?.summary(config, first, last)?;
+    print!("{}", summary);
+    Ok(())
+}
+
+/// The data of a poll, kept to redraw it in another format
+struct Poll {
+    /// When the poll succeeded, counting the time the machine was suspended
+    polled_at: DateTime<Utc>,
+    location: Location,
+    weather: Box<dyn Weather>,
+    pollen: Option<Box<dyn Pollen>>,
+    indoor: Option<SensorReading>,
 }
 
 struct App {
     config: Config,
-    current_location: Box<dyn CurrentLocation>,
+    location_service: LocationService,
+    history_service: HistoryService,
+    record_service: Option<RecordService>,
+    schedule_service: ScheduleService,
     current_weather: Box<dyn CurrentWeather>,
+    current_pollen: Box<dyn CurrentPollen>,
+    sensor: Option<Box<dyn Sensor>>,
+    args: CliArgs,
+    /// Index of the displayed format among the formats
+    format_index: usize,
+    last_poll: Option<Poll>,
+    /// When the weather was polled successfully for the last time
+    polled_at: Option<time::Instant>,
+    failed: bool,
+    /// What the responses of the last poll tell about when to poll again
+    freshness: Freshness,
+    /// Whether polling waits for the session to be unlocked or to wake up
+    paused: bool,
+    events: Receiver<Event>,
+    event_sender: Sender<Event>,
 }
 
 impl App {
-    fn new(config: Config) -> Self {
+    fn new(config: Config, args: CliArgs) -> Result<Self> {
         let current_location = Self::current_location(&config.location);
-        let current_weather = Self::current_weather(&config.weather.provider);
+        let location_service = LocationService::new(&config.location, current_location);
+        let history_service = HistoryService::new(&config.history);
+        let record_service = if config.recorder.enabled {
+            Some(RecordService::new(&config)?)
+        } else {
+            None
+        };
+        let schedule_service = ScheduleService::new(&config.schedule);
+        let current_weather = Self::current_weather(&config.weather, &config.language);
+        let current_pollen = Self::current_pollen(&config.pollen);
+        let sensor = Self::sensor(&config.sensor);
+        let (event_sender, events) = mpsc::channel();
 
-        Self {
+        Ok(Self {
             config,
-            current_location,
+            location_service,
+            history_service,
+            record_service,
+            schedule_service,
             current_weather,
+            current_pollen,
+            sensor,
+            args,
+            format_index: 0,
+            last_poll: None,
+            polled_at: None,
+            failed: false,
+            freshness: Freshness::default(),
+            paused: false,
+            events,
+            event_sender,
+        })
+    }
+
+    /// Applies a reloaded config, keeping the state of services whose config didn't change
+    fn apply(&mut self, config: Config) -> Result<()> {
+        let record_service = if config.recorder.enabled {
+            Some(RecordService::new(&config)?)
+        } else {
+            None
+        };
+        if config.location != self.config.location {
+            let current_location = Self::current_location(&config.location);
+            self.location_service = LocationService::new(&config.location, current_location);
+        }
+        if config.history != self.config.history {
+            self.history_service = HistoryService::new(&config.history);
+        }
+        if config.pollen != self.config.pollen {
+            self.current_pollen = Self::current_pollen(&config.pollen);
+        }
+        if config.sensor != self.config.sensor {
+            self.sensor = Self::sensor(&config.sensor);
+        }
+        self.schedule_service = ScheduleService::new(&config.schedule);
+        // rebuilding the provider would drop its state, e.g. Netatmo's access token
+        if Self::weather_changed(&self.config, &config) {
+            self.current_weather = Self::current_weather(&config.weather, &config.language);
+        }
+        self.record_service = record_service;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Whether the selected weather provider is set up differently, ignoring the sections
+    /// of the other providers
+    fn weather_changed(old: &Config, new: &Config) -> bool {
+        let (old_weather, new_weather) = (&old.weather, &new.weather);
+        let language_changed = old.language != new.language;
+        match new_weather.provider {
+            _ if old_weather.provider != new_weather.provider => true,
+            WeatherProvider::OpenWeatherMap => {
+                old_weather.openweathermap != new_weather.openweathermap || language_changed
+            }
+            WeatherProvider::WeatherUnderground => {
+                old_weather.weather_underground != new_weather.weather_underground
+            }
+            WeatherProvider::Netatmo => old_weather.netatmo != new_weather.netatmo,
+            WeatherProvider::Ecowitt => old_weather.ecowitt != new_weather.ecowitt,
+            WeatherProvider::Command => {
+                old_weather.command != new_weather.command || language_changed
+            }
         }
     }
 
     fn current_location(location_config: &LocationConfig) -> Box<dyn CurrentLocation> {
         match &location_config.provider {
-            LocationProvider::IpApi => Box::new(IpApi::new()),
+            LocationProvider::IpApi => Box::new(IpApi::new(http_client())),
             LocationProvider::Manual => Box::new(Manual::new(&location_config.location)),
+            LocationProvider::Network => Box::new(Network::new(&location_config.networks)),
+            LocationProvider::Command => {
+                Box::new(location::command::Command::new(&location_config.command))
+            }
         }
     }
 
-    fn current_weather(provider: &WeatherProvider) -> Box<dyn CurrentWeather> {
-        match provider {
-            WeatherProvider::OpenWeatherMap => Box::new(OpenWeatherMap::new()),
+    fn current_weather(
+        weather_config: &WeatherConfig,
+        language: &Language,
+    ) -> Box<dyn CurrentWeather> {
+        match weather_config.provider {
+            WeatherProvider::OpenWeatherMap => Box::new(OpenWeatherMap::new(
+                &weather_config.openweathermap,
+                language,
+                http_client(),
+            )),
+            WeatherProvider::WeatherUnderground => Box::new(WeatherUnderground::new(
+                &weather_config.weather_underground,
+                http_client(),
+            )),
+            WeatherProvider::Netatmo => {
+                Box::new(Netatmo::new(&weather_config.netatmo, http_client()))
+            }
+            WeatherProvider::Ecowitt => {
+                Box::new(Ecowitt::new(&weather_config.ecowitt, http_client()))
+            }
+            WeatherProvider::Command => Box::new(weather::command::Command::new(
+                &weather_config.command,
+                language,
+            )),
         }
     }
 
-    fn run(&self) -> Result<()> {
+    fn current_pollen(pollen_config: &PollenConfig) -> Box<dyn CurrentPollen> {
+        match pollen_config.provider {
+            PollenProvider::OpenMeteo => Box::new(OpenMeteo::new(http_client())),
+        }
+    }
+
+    fn sensor(sensor_config: &SensorConfig) -> Option<Box<dyn Sensor>> {
+        let unit = &sensor_config.unit;
+        match &sensor_config.provider {
+            SensorProvider::Disabled => None,
+            SensorProvider::File => {
+                Some(Box::new(sensor::file::File::new(&sensor_config.path, unit)))
+            }
+            SensorProvider::Command => Some(Box::new(sensor::command::Command::new(
+                &sensor_config.command,
+                unit,
+            ))),
+            SensorProvider::Mqtt => {
+                Some(Box::new(sensor::mqtt::Mqtt::new(&sensor_config.mqtt, unit)))
+            }
+        }
+    }
+
+    fn run(&mut self) -> Result<()> {
+        if self.config.interval.0 >= 0 {
+            self.watch();
+        }
+        let result = self.poll();
+        pid_file::remove();
+        socket::remove();
+        result
+    }
+
+    fn poll(&mut self) -> Result<()> {
         loop {
-            debug!("Polling current location...");
-            let location = self.current_location.location()?;
-            debug!("{:#?}", location);
-            debug!("Polling current weather...");
-            let weather = self.weather(location)?;
-            println!("{}", weather);
+            let result = self.location_service.location().and_then(|location| {
+                debug!("Polling current weather...");
+                self.weather(location)
+            });
+            self.failed = result.is_err();
+            self.freshness = match &result {
+                Ok(poll) => poll.weather.freshness(),
+                Err(err) => Freshness {
+                    expires: None,
+                    retry_after: err.downcast_ref::<RateLimited>().map(|limited| limited.0),
+                },
+            };
+            match result {
+                Ok(poll) => {
+                    self.last_poll = Some(poll);
+                    self.polled_at = Some(time::Instant::now());
+                }
+                // a single run reports the error, a running bar keeps polling
+                Err(err) if self.config.interval.0 < 0 => return Err(err),
+                Err(err) => error!("{}", err),
+            }
+            // a failed poll keeps showing the last weather, marked as stale
+            let formatted = self.format();
+            println!("{}", formatted.as_deref().unwrap_or(UNAVAILABLE));
 
-            self.sleep();
+            if !self.wait() {
+                debug!("Exiting...");
+                return Ok(());
+            }
+        }
+    }
+
+    /// Reloads the config when its file changes or on SIGHUP
+    fn watch(&self) {
+        let path = config_service::config_path(&self.args);
+        if let Some(path) = path.filter(|path| !path.as_os_str().is_empty()) {
+            if let Err(err) = watcher::watch(&path, self.event_sender.clone()) {
+                warn!("Couldn't watch config file: {}", err);
+            }
+        }
+        // signals have to be handled before announcing the PID
+        if let Err(err) = signals::forward(self.event_sender.clone()) {
+            warn!("Couldn't handle signals: {}", err);
+        } else if let Err(err) = pid_file::create() {
+            warn!("Couldn't write PID file: {}", err);
+        }
+        if let Err(err) = socket::listen(self.event_sender.clone()) {
+            warn!("Couldn't listen for commands: {}", err);
+        }
+        clock::watch(self.event_sender.clone());
+        if let Err(err) = connectivity::watch(self.event_sender.clone()) {
+            warn!("Couldn't watch network connectivity: {}", err);
+        }
+        if self.config.schedule.pause_when_locked {
+            if let Err(err) = session::watch(self.event_sender.clone()) {
+                warn!("Couldn't watch session for locking and suspending: {}", err);
+            }
         }
     }
 
-    fn weather(&self, location: Location) -> Result<String> {
-        let weather = self
-            .current_weather
-            .weather(&location, &self.config.weather.api_key)?;
-        let formatted = FormatService::new(&self.config, location, weather).format();
-        Ok(formatted)
+    /// Returns whether the config changed
+    fn reload(&mut self) -> bool {
+        debug!("Reloading config...");
+        let result = config_service::reload(&self.args).and_then(|config| {
+            if config == self.config {
+                debug!("Config unchanged");
+                Ok(false)
+            } else {
+                self.apply(config).map(|_| true)
+            }
+        });
+        result.unwrap_or_else(|err| {
+            error!("Keeping the current config: {}", err);
+            false
+        })
     }
 
-    fn sleep(&self) {
-        if self.config.interval.0 >= 0 {
-            debug!("Sleeping for {}s...", self.config.interval.0);
-            thread::sleep(time::Duration::from_secs(self.config.interval.0 as u64));
+    /// The tags of the formats and the recorder, which decide what the providers query
+    fn used_tags(&self) -> HashSet<String> {
+        let mut tags = FormatService::used_tags(&self.config);
+        if let Some(record_service) = &self.record_service {
+            tags.extend(record_service.used_tags());
+        }
+        tags
+    }
+
+    fn weather(&mut self, location: Location) -> Result<Poll> {
+        let tags = self.used_tags();
+        let weather = self.current_weather.weather(&location, &tags)?;
+        // pollen data is only fetched on demand to avoid an extra request
+        let pollen = if POLLEN_TAGS.iter().any(|tag| tags.contains(*tag)) {
+            debug!("Polling current pollen...");
+            self.current_pollen
+                .pollen(&location)
+                .inspect_err(|err| warn!("Couldn't poll pollen: {}", err))
+                .ok()
         } else {
+            None
+        };
+        let indoor = match &self.sensor {
+            Some(sensor) if SENSOR_TAGS.iter().any(|tag| tags.contains(*tag)) => {
+                debug!("Reading sensor...");
+                sensor
+                    .reading()
+                    .map_err(|err| warn!("Error reading sensor: {}", err))
+                    .ok()
+            }
+            _ => None,
+        };
+        self.history_service.record(weather.as_ref());
+        if let Some(record_service) = &mut self.record_service {
+            record_service.record(&location, weather.as_ref(), pollen.as_deref());
+        }
+        Ok(Poll {
+            polled_at: Utc::now(),
+            location,
+            weather,
+            pollen,
+            indoor,
+        })
+    }
+
+    /// Formats the last poll in the current format, or in the stale format
+    /// once the last successful poll is too old to be shown as current
+    fn format(&self) -> Option<String> {
+        let poll = self.last_poll.as_ref()?;
+        let age = Utc::now() - poll.polled_at;
+        let (format, stale) =
+            FormatService::select_format(&self.config, self.format_index, self.failed, age);
+        let mut formatted = FormatService::new(
+            &self.config,
+            format,
+            &poll.location,
+            poll.weather.as_ref(),
+            poll.pollen.as_deref(),
+            &self.history_service,
+            poll.indoor,
+        )
+        .format();
+        if stale {
+            formatted.push_str(&self.config.stale_marker.0);
+        }
+        Some(formatted)
+    }
+
+    /// Whether the weather shown may have changed since it was polled,
+    /// as the monotonic clock doesn't count the time the machine was suspended
+    fn outdated(&self) -> bool {
+        self.failed
+            || self
+                .polled_at
+                .is_none_or(|polled_at| polled_at.elapsed() > clock::CHECK_INTERVAL)
+    }
+
+    /// What the last poll and the machine tell about when to poll next
+    fn circumstances(&self) -> Circumstances {
+        let weather = self.last_poll.as_ref().map(|poll| poll.weather.as_ref());
+        Circumstances {
+            failed: self.failed,
+            eventful: weather.is_some_and(|weather| weather.is_eventful()),
+            night: weather.and_then(|weather| weather.is_day()) == Some(false),
+            // only read if it makes a difference
+            on_battery: self.config.schedule.adaptive && power::on_battery(),
+            update_period: self.current_weather.update_period(),
+            freshness: self.freshness,
+        }
+    }
+
+    /// Sleeps until the next poll is due or an event requires one,
+    /// returns whether wedder keeps running
+    fn wait(&mut self) -> bool {
+        if self.config.interval.0 < 0 {
             debug!("Exiting because of negative interval...");
-            process::exit(0);
+            return false;
+        }
+        let now = Utc::now();
+        let next_poll =
+            self.schedule_service
+                .next_poll(&self.config.interval, &self.circumstances(), now);
+        let delay = (next_poll - now).to_std().unwrap_or_default();
+        debug!("Sleeping for {}s...", delay.as_secs());
+        let next_poll = time::Instant::now() + delay;
+        loop {
+            let timeout = next_poll.saturating_duration_since(time::Instant::now());
+            let event = if self.paused {
+                self.events.recv().ok()
+            } else {
+                self.events.recv_timeout(timeout).ok()
+            };
+            let Some(event) = event else {
+                return true;
+            };
+            debug!("Handling {:?}", event);
+            let poll = match event {
+                Event::Reload => {
+                    // editors often write a file several times when saving
+                    thread::sleep(RELOAD_DELAY);
+                    self.reload()
+                }
+                Event::Refresh => true,
+                Event::NextFormat => {
+                    self.format_index += 1;
+                    // redraw without polling, unless there is no weather to show
+                    match self.format() {
+                        Some(formatted) => {
+                            println!("{}", formatted);
+                            false
+                        }
+                        None => true,
+                    }
+                }
+                Event::Pause => {
+                    self.paused = true;
+                    false
+                }
+                // the weather shown is from before the session went away
+                Event::Resume => {
+                    self.paused = false;
+                    true
+                }
+                // waking up also resumes the session, which may have polled already
+                Event::ClockJump | Event::Online => self.outdated(),
+                Event::Exit => return false,
+            };
+            // resuming polls anyway, so polls requested while paused are deferred until then
+            if poll && !self.paused {
+                return true;
+            }
+            if poll {
+                debug!("Deferring poll until the session resumes...");
+            }
         }
     }
 }
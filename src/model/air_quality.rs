@@ -0,0 +1,227 @@
+use serde::Deserialize;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::model::config::AqiUnit;
+use crate::model::weather::Aqi;
+
+/// Molar volume of air in liters at 25°C and 1 atm
+const MOLAR_VOLUME: f32 = 24.45;
+
+/// Concentration in µg/m³
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq)]
+pub struct Concentration(pub f32);
+
+impl Concentration {
+    /// Parts per billion of a gas with the given molecular weight
+    fn ppb(&self, molecular_weight: f32) -> f32 {
+        self.0 * MOLAR_VOLUME / molecular_weight
+    }
+}
+
+impl Display for Concentration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1}", self.0)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Copy, Clone, PartialEq)]
+pub struct Pollutants {
+    pub pm2_5: Option<Concentration>,
+    pub pm10: Option<Concentration>,
+    pub o3: Option<Concentration>,
+    pub no2: Option<Concentration>,
+    pub so2: Option<Concentration>,
+    pub co: Option<Concentration>,
+    pub nh3: Option<Concentration>,
+}
+
+/// Index ranges of the US EPA AQI categories
+const EPA_INDEX: [(f32, f32); 6] = [
+    (0.0, 50.0),
+    (51.0, 100.0),
+    (101.0, 150.0),
+    (151.0, 200.0),
+    (201.0, 300.0),
+    (301.0, 500.0),
+];
+/// µg/m³
+const EPA_PM2_5: [(f32, f32); 6] = [
+    (0.0, 9.0),
+    (9.1, 35.4),
+    (35.5, 55.4),
+    (55.5, 125.4),
+    (125.5, 225.4),
+    (225.5, 325.4),
+];
+/// µg/m³
+const EPA_PM10: [(f32, f32); 6] = [
+    (0.0, 54.0),
+    (55.0, 154.0),
+    (155.0, 254.0),
+    (255.0, 354.0),
+    (355.0, 424.0),
+    (425.0, 604.0),
+];
+/// ppb, 8-hour
+const EPA_O3: [(f32, f32); 5] = [
+    (0.0, 54.0),
+    (55.0, 70.0),
+    (71.0, 85.0),
+    (86.0, 105.0),
+    (106.0, 200.0),
+];
+/// ppb
+const EPA_NO2: [(f32, f32); 6] = [
+    (0.0, 53.0),
+    (54.0, 100.0),
+    (101.0, 360.0),
+    (361.0, 649.0),
+    (650.0, 1249.0),
+    (1250.0, 2049.0),
+];
+/// ppb
+const EPA_SO2: [(f32, f32); 6] = [
+    (0.0, 35.0),
+    (36.0, 75.0),
+    (76.0, 185.0),
+    (186.0, 304.0),
+    (305.0, 604.0),
+    (605.0, 1004.0),
+];
+/// ppb
+const EPA_CO: [(f32, f32); 6] = [
+    (0.0, 4400.0),
+    (4500.0, 9400.0),
+    (9500.0, 12400.0),
+    (12500.0, 15400.0),
+    (15500.0, 30400.0),
+    (30500.0, 50400.0),
+];
+
+/// Grid of the hourly European CAQI in µg/m³ at the indices 0, 25, 50, 75 and 100
+const CAQI_NO2: [f32; 5] = [0.0, 50.0, 100.0, 200.0, 400.0];
+const CAQI_PM10: [f32; 5] = [0.0, 25.0, 50.0, 90.0, 180.0];
+const CAQI_O3: [f32; 5] = [0.0, 60.0, 120.0, 180.0, 240.0];
+const CAQI_PM2_5: [f32; 5] = [0.0, 15.0, 30.0, 55.0, 110.0];
+
+impl Pollutants {
+    /// US EPA AQI from 0 to 500, using the current concentrations in place of the averages
+    pub fn us_epa(&self) -> Option<Aqi> {
+        let sub_indices = [
+            self.pm2_5.map(|c| epa_index(c.0, &EPA_PM2_5)),
+            self.pm10.map(|c| epa_index(c.0, &EPA_PM10)),
+            self.o3.map(|c| epa_index(c.ppb(48.0), &EPA_O3)),
+            self.no2.map(|c| epa_index(c.ppb(46.01), &EPA_NO2)),
+            self.so2.map(|c| epa_index(c.ppb(64.07), &EPA_SO2)),
+            self.co.map(|c| epa_index(c.ppb(28.01), &EPA_CO)),
+        ];
+        max(&sub_indices)
+    }
+
+    /// Hourly European Common Air Quality Index, usually from 0 to 100
+    pub fn caqi(&self) -> Option<Aqi> {
+        let sub_indices = [
+            self.no2.map(|c| caqi_index(c.0, &CAQI_NO2)),
+            self.pm10.map(|c| caqi_index(c.0, &CAQI_PM10)),
+            self.o3.map(|c| caqi_index(c.0, &CAQI_O3)),
+            self.pm2_5.map(|c| caqi_index(c.0, &CAQI_PM2_5)),
+        ];
+        max(&sub_indices)
+    }
+}
+
+fn max(sub_indices: &[Option<f32>]) -> Option<Aqi> {
+    sub_indices
+        .iter()
+        .flatten()
+        .copied()
+        .reduce(f32::max)
+        .map(|index| Aqi(index.round()))
+}
+
+fn epa_index(concentration: f32, breakpoints: &[(f32, f32)]) -> f32 {
+    let band = breakpoints
+        .iter()
+        .position(|(_, high)| concentration <= *high);
+    let Some(band) = band else {
+        return EPA_INDEX[breakpoints.len() - 1].1;
+    };
+    let (c_low, c_high) = breakpoints[band];
+    let (i_low, i_high) = EPA_INDEX[band];
+    let index = (i_high - i_low) / (c_high - c_low) * (concentration - c_low) + i_low;
+    // concentrations between two bands
+    index.max(i_low)
+}
+
+fn caqi_index(concentration: f32, grid: &[f32; 5]) -> f32 {
+    let segment = grid[1..]
+        .iter()
+        .position(|high| concentration <= *high)
+        // extrapolate the last segment above 100
+        .unwrap_or(grid.len() - 2);
+    let (low, high) = (grid[segment], grid[segment + 1]);
+    25.0 * segment as f32 + 25.0 * (concentration - low) / (high - low)
+}
+
+/// Key of the category label the index falls into on the given scale
+pub fn category(aqi: Aqi, unit: &AqiUnit) -> &'static str {
+    let index = aqi.0.round();
+    match unit {
+        AqiUnit::Owm => match index as i32 {
+            ..=1 => "good",
+            2 => "fair",
+            3 => "moderate",
+            4 => "poor",
+            _ => "very_poor",
+        },
+        AqiUnit::UsEpa => match index {
+            i if i <= 50.0 => "good",
+            i if i <= 100.0 => "moderate",
+            i if i <= 150.0 => "unhealthy_for_sensitive_groups",
+            i if i <= 200.0 => "unhealthy",
+            i if i <= 300.0 => "very_unhealthy",
+            _ => "hazardous",
+        },
+        AqiUnit::Caqi => match index {
+            i if i < 25.0 => "very_low",
+            i if i < 50.0 => "low",
+            i if i < 75.0 => "medium",
+            i if i <= 100.0 => "high",
+            _ => "very_high",
+        },
+    }
+}
+
+#[test]
+fn air_quality_indices() {
+    let pollutants = Pollutants {
+        pm2_5: Some(Concentration(35.4)),
+        pm10: Some(Concentration(20.0)),
+        o3: Some(Concentration(90.0)),
+        no2: Some(Concentration(30.0)),
+        so2: None,
+        co: None,
+        nh3: None,
+    };
+
+    assert_eq!(pollutants.us_epa().unwrap().0, 100.0);
+    // PM2.5 dominates with 50 + 5.4/25 * 25
+    assert_eq!(pollutants.caqi().unwrap().0, 55.0);
+    assert!(Pollutants::default().us_epa().is_none());
+
+    let hazardous = Pollutants {
+        pm2_5: Some(Concentration(1000.0)),
+        ..Default::default()
+    };
+    assert_eq!(hazardous.us_epa().unwrap().0, 500.0);
+    assert_eq!(
+        category(hazardous.us_epa().unwrap(), &AqiUnit::UsEpa),
+        "hazardous"
+    );
+    assert_eq!(
+        category(hazardous.caqi().unwrap(), &AqiUnit::Caqi),
+        "very_high"
+    );
+    assert_eq!(category(Aqi(1.33), &AqiUnit::Owm), "good");
+}
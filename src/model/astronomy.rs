@@ -0,0 +1,33 @@
+use strum_macros::Display;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum MoonPhase {
+    NewMoon,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    FullMoon,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhase {
+    /// Maps the fraction of the lunar cycle elapsed since the last new moon onto a phase
+    pub fn from_cycle(cycle: f64) -> Self {
+        let phases = [
+            Self::NewMoon,
+            Self::WaxingCrescent,
+            Self::FirstQuarter,
+            Self::WaxingGibbous,
+            Self::FullMoon,
+            Self::WaningGibbous,
+            Self::LastQuarter,
+            Self::WaningCrescent,
+        ];
+        // each of the main phases is centered around its exact point in the cycle
+        let index = (cycle.rem_euclid(1.0) * 8.0 + 0.5) as usize % phases.len();
+        phases[index]
+    }
+}
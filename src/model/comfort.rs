@@ -0,0 +1,130 @@
+use serde::Deserialize;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::model::weather::{Kelvin, Ms, Percentage};
+
+/// Magnus coefficients over water
+const MAGNUS_A: f32 = 17.62;
+const MAGNUS_B: f32 = 243.12;
+
+/// Water vapor density in g/m³
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq)]
+pub struct AbsoluteHumidity(pub f32);
+
+impl Display for AbsoluteHumidity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1}", self.0)
+    }
+}
+
+/// Magnus formula
+pub fn dew_point(temp: Kelvin, humidity: Percentage) -> Option<Kelvin> {
+    if humidity.0 <= 0.0 {
+        return None;
+    }
+    let t = temp.celsius();
+    let gamma = (humidity.0 / 100.0).ln() + MAGNUS_A * t / (MAGNUS_B + t);
+    Some(Kelvin::from_celsius(MAGNUS_B * gamma / (MAGNUS_A - gamma)))
+}
+
+/// Algorithm of the US National Weather Service, only defined from 27°C
+pub fn heat_index(temp: Kelvin, humidity: Percentage) -> Option<Kelvin> {
+    if temp.celsius() < 27.0 {
+        return None;
+    }
+    let t = temp.fahrenheit();
+    let rh = humidity.0;
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    if (simple + t) / 2.0 < 80.0 {
+        return Some(Kelvin::from_fahrenheit(simple));
+    }
+
+    let mut index = -42.379 + 2.049_015_2 * t + 10.143_331 * rh
+        - 0.224_755_4 * t * rh
+        - 0.006_837_83 * t * t
+        - 0.054_817_17 * rh * rh
+        + 0.001_228_74 * t * t * rh
+        + 0.000_852_82 * t * rh * rh
+        - 0.000_001_99 * t * t * rh * rh;
+    if rh < 13.0 && (80.0..=112.0).contains(&t) {
+        index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+        index += (rh - 85.0) / 10.0 * (87.0 - t) / 5.0;
+    }
+    Some(Kelvin::from_fahrenheit(index))
+}
+
+/// Formula of Environment Canada and the US National Weather Service,
+/// only defined up to 10°C and above 4.8 km/h
+pub fn wind_chill(temp: Kelvin, wind_speed: Ms) -> Option<Kelvin> {
+    let t = temp.celsius();
+    let v = wind_speed.0 * 3.6;
+    if t > 10.0 || v <= 4.8 {
+        return None;
+    }
+    let v = v.powf(0.16);
+    Some(Kelvin::from_celsius(
+        13.12 + 0.6215 * t - 11.37 * v + 0.3965 * t * v,
+    ))
+}
+
+/// Formula of Environment Canada, in degrees like the temperature,
+/// which only reports it from 20°C
+pub fn humidex(temp: Kelvin, dew_point: Kelvin) -> Option<Kelvin> {
+    if temp.celsius() < 20.0 {
+        return None;
+    }
+    let vapor_pressure = 6.11 * (5417.753 * (1.0 / 273.16 - 1.0 / dew_point.0)).exp();
+    Some(Kelvin::from_celsius(
+        temp.celsius() + 0.5555 * (vapor_pressure - 10.0),
+    ))
+}
+
+pub fn absolute_humidity(temp: Kelvin, humidity: Percentage) -> AbsoluteHumidity {
+    let t = temp.celsius();
+    let saturation_pressure = 6.112 * (17.67 * t / (t + 243.5)).exp();
+    AbsoluteHumidity(saturation_pressure * humidity.0 * 2.1674 / temp.0)
+}
+
+#[test]
+fn comfort() {
+    let assert_close = |actual: f32, expected: f32| {
+        assert!(
+            (actual - expected).abs() < 0.2,
+            "{} != {}",
+            actual,
+            expected
+        );
+    };
+
+    let dew_point = dew_point(Kelvin::from_celsius(20.0), Percentage(50.0)).unwrap();
+    assert_close(dew_point.celsius(), 9.3);
+    // NWS heat index chart
+    assert_close(
+        heat_index(Kelvin::from_fahrenheit(90.0), Percentage(70.0))
+            .unwrap()
+            .fahrenheit(),
+        105.9,
+    );
+    assert_close(
+        wind_chill(Kelvin::from_celsius(-10.0), Ms::from_kmh(30.0))
+            .unwrap()
+            .celsius(),
+        -19.5,
+    );
+    assert!(wind_chill(Kelvin::from_celsius(15.0), Ms(10.0)).is_none());
+    assert!(wind_chill(Kelvin::from_celsius(-10.0), Ms::from_kmh(3.0)).is_none());
+    assert!(heat_index(Kelvin::from_celsius(20.0), Percentage(70.0)).is_none());
+    assert!(humidex(Kelvin::from_celsius(15.0), Kelvin::from_celsius(10.0)).is_none());
+    assert_close(
+        humidex(Kelvin::from_celsius(30.0), Kelvin::from_celsius(15.0))
+            .unwrap()
+            .celsius(),
+        33.9,
+    );
+    assert_close(
+        absolute_humidity(Kelvin::from_celsius(20.0), Percentage(50.0)).0,
+        8.6,
+    );
+}
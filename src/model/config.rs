@@ -1,29 +1,86 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::num::ParseIntError;
 use std::str::FromStr;
 use std::string::ParseError;
 use strum_macros::EnumString;
 
-use crate::model::location::{Location, LocationProvider};
+use crate::model::language::Language;
+use crate::model::location::{Location, LocationProvider, NetworkLocation};
+use crate::model::pollen::PollenProvider;
+use crate::model::sensor::SensorProvider;
 use crate::model::weather::WeatherProvider;
 
-#[derive(Default, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Config {
     #[serde(default)]
     pub format: Format,
+    /// Formats cycled through on SIGUSR2, replacing the format if set
+    #[serde(default)]
+    pub formats: Vec<Format>,
+    /// Appended to the last weather while polling fails
+    #[serde(default)]
+    pub stale_marker: StaleMarker,
+    /// Displayed instead of the formats once the last successful poll is older than the max age
+    #[serde(default)]
+    pub stale_format: Option<Format>,
+    #[serde(default)]
+    pub max_age: MaxAge,
     #[serde(default)]
     pub interval: Interval,
     #[serde(default)]
+    pub schedule: ScheduleConfig,
+    #[serde(default)]
     pub units: Units,
     #[serde(default)]
+    pub time_zone: TimeZone,
+    #[serde(default)]
+    pub language: Language,
+    #[serde(default)]
     pub weather: WeatherConfig,
     #[serde(default)]
+    pub pollen: PollenConfig,
+    #[serde(default)]
     pub location: LocationConfig,
     #[serde(default)]
+    pub sensor: SensorConfig,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub recorder: RecorderConfig,
+    #[serde(default)]
+    pub tags: CustomTags,
+    #[serde(default)]
     pub icons: Icons,
 }
 
+impl Config {
+    /// The formats to cycle through, which is just the format unless formats are set
+    pub fn formats(&self) -> impl Iterator<Item = &Format> {
+        let single = self.formats.is_empty().then_some(&self.format);
+        single.into_iter().chain(&self.formats)
+    }
+
+    /// The format at the index of the formats, wrapping around
+    pub fn format_at(&self, index: usize) -> &Format {
+        match self.formats.len() {
+            0 => &self.format,
+            count => &self.formats[index % count],
+        }
+    }
+
+    /// In seconds, the longest the schedule may wait between polls
+    pub fn longest_interval(&self) -> i32 {
+        let interval = self.interval.0.max(0);
+        if !self.schedule.adaptive {
+            return interval;
+        }
+        interval
+            .max(self.schedule.night_interval.0)
+            .max(self.schedule.battery_interval.0)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Format(pub String);
 
@@ -40,6 +97,25 @@ impl FromStr for Format {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct StaleMarker(pub String);
+
+impl Default for StaleMarker {
+    fn default() -> Self {
+        Self(" ?".to_string())
+    }
+}
+
+/// In seconds since the last successful poll
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct MaxAge(pub u32);
+
+impl Default for MaxAge {
+    fn default() -> Self {
+        Self(3600)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Interval(pub i32);
 
@@ -56,6 +132,64 @@ impl FromStr for Interval {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ScheduleConfig {
+    /// Adapts the interval to the weather, the time of day, the power supply
+    /// and the caching of the provider
+    #[serde(default)]
+    pub adaptive: bool,
+    /// Interval while it rains, snows or storms
+    #[serde(default = "ScheduleConfig::precipitation_interval")]
+    pub precipitation_interval: Interval,
+    /// Interval between sunset and sunrise
+    #[serde(default = "ScheduleConfig::night_interval")]
+    pub night_interval: Interval,
+    /// Interval while the machine runs on battery
+    #[serde(default = "ScheduleConfig::battery_interval")]
+    pub battery_interval: Interval,
+    /// Polls right after providers with known update times publish new data
+    #[serde(default = "ScheduleConfig::align")]
+    pub align: bool,
+    /// Pauses polling while the session is locked or suspended
+    #[serde(default = "ScheduleConfig::pause_when_locked")]
+    pub pause_when_locked: bool,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            adaptive: false,
+            precipitation_interval: Self::precipitation_interval(),
+            night_interval: Self::night_interval(),
+            battery_interval: Self::battery_interval(),
+            align: Self::align(),
+            pause_when_locked: Self::pause_when_locked(),
+        }
+    }
+}
+
+impl ScheduleConfig {
+    fn precipitation_interval() -> Interval {
+        Interval(120)
+    }
+
+    fn night_interval() -> Interval {
+        Interval(900)
+    }
+
+    fn battery_interval() -> Interval {
+        Interval(900)
+    }
+
+    fn align() -> bool {
+        true
+    }
+
+    fn pause_when_locked() -> bool {
+        true
+    }
+}
+
 #[derive(Default, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Units {
     #[serde(default)]
@@ -66,6 +200,8 @@ pub struct Units {
     pub distance: DistanceUnit,
     #[serde(default)]
     pub precipitation: PrecipitationUnit,
+    #[serde(default)]
+    pub aqi: AqiUnit,
 }
 
 #[derive(Debug, Serialize, Deserialize, EnumString, Eq, PartialEq, Clone)]
@@ -86,6 +222,7 @@ pub enum WindSpeedUnit {
     Ms,
     Kmh,
     Mph,
+    Beaufort,
 }
 
 impl Default for WindSpeedUnit {
@@ -119,20 +256,307 @@ impl Default for PrecipitationUnit {
     }
 }
 
+/// The scale the air quality index is displayed in
+#[derive(Default, Debug, Serialize, Deserialize, EnumString, Eq, PartialEq, Clone)]
+pub enum AqiUnit {
+    /// OpenWeatherMap's scale from 1 (good) to 5 (very poor)
+    #[default]
+    Owm,
+    /// US EPA scale from 0 to 500
+    UsEpa,
+    /// European Common Air Quality Index from 0 to 100
+    Caqi,
+}
+
+/// The time zone times like sunrise and sunset are displayed in
+#[derive(Default, Debug, Serialize, Deserialize, EnumString, Eq, PartialEq, Clone)]
+pub enum TimeZone {
+    /// Time zone of the machine wedder is running on
+    #[default]
+    Machine,
+    /// Time zone of the location the weather is displayed for, if the weather provider reports it
+    Location,
+}
+
 #[derive(Default, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct WeatherConfig {
     #[serde(default)]
     pub provider: WeatherProvider,
+    /// Deprecated, moved to the OpenWeatherMap section
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub openweathermap: OpenWeatherMapConfig,
+    #[serde(default)]
+    pub weather_underground: WeatherUndergroundConfig,
+    #[serde(default)]
+    pub netatmo: NetatmoConfig,
+    #[serde(default)]
+    pub ecowitt: EcowittConfig,
+    #[serde(default)]
+    pub command: CommandConfig,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct OpenWeatherMapConfig {
     #[serde(default)]
     pub api_key: String,
+    /// Base URL of the API, e.g. of a proxy or a compatible service
+    #[serde(default = "OpenWeatherMapConfig::endpoint")]
+    pub endpoint: String,
+    /// Version of the current weather and air pollution APIs
+    #[serde(default = "OpenWeatherMapConfig::api_version")]
+    pub api_version: String,
+    /// Query the alerts of the One Call API, which needs a separate subscription
+    #[serde(default)]
+    pub alerts: bool,
+}
+
+impl Default for OpenWeatherMapConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            endpoint: Self::endpoint(),
+            api_version: Self::api_version(),
+            alerts: false,
+        }
+    }
+}
+
+impl OpenWeatherMapConfig {
+    fn endpoint() -> String {
+        "http://api.openweathermap.org".to_string()
+    }
+
+    fn api_version() -> String {
+        "2.5".to_string()
+    }
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct WeatherUndergroundConfig {
+    #[serde(default)]
+    pub station_id: String,
+    #[serde(default)]
+    pub api_key: String,
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct NetatmoConfig {
+    #[serde(default)]
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: String,
+    /// Only used initially, the rotated refresh tokens are cached
+    #[serde(default)]
+    pub refresh_token: String,
+    /// MAC address of the station, defaults to the first station of the account
+    #[serde(default)]
+    pub device_id: String,
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct EcowittConfig {
+    /// Host name or IP address of the gateway in the local network
+    #[serde(default)]
+    pub host: String,
+}
+
+/// An external program printing the weather or location as JSON, or a sensor reading
+#[derive(Default, Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct CommandConfig {
+    #[serde(default)]
+    pub command: String,
+    #[serde(default)]
+    pub timeout: Timeout,
+}
+
+/// In seconds
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Timeout(pub u64);
+
+impl Default for Timeout {
+    fn default() -> Self {
+        Self(10)
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct PollenConfig {
+    #[serde(default)]
+    pub provider: PollenProvider,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct LocationConfig {
     #[serde(default)]
     pub provider: LocationProvider,
+    #[serde(default)]
+    pub interval: Interval,
+    #[serde(default)]
+    pub min_distance: MinDistance,
     #[serde(default, flatten)]
     pub location: Location,
+    #[serde(default)]
+    pub networks: Vec<NetworkLocation>,
+    #[serde(default)]
+    pub command: CommandConfig,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct MinDistance(pub u32);
+
+impl Default for MinDistance {
+    fn default() -> Self {
+        Self(1000)
+    }
+}
+
+impl FromStr for MinDistance {
+    type Err = ParseIntError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u32::from_str(s).map(Self)
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct SensorConfig {
+    #[serde(default)]
+    pub provider: SensorProvider,
+    /// The unit of the temperatures the sensor reports
+    #[serde(default)]
+    pub unit: TemperatureUnit,
+    /// File to read for the File provider
+    #[serde(default)]
+    pub path: String,
+    /// Shell command to run for the Command provider
+    #[serde(default)]
+    pub command: CommandConfig,
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct MqttConfig {
+    #[serde(default = "MqttConfig::host")]
+    pub host: String,
+    #[serde(default = "MqttConfig::port")]
+    pub port: u16,
+    #[serde(default)]
+    pub topic: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            host: Self::host(),
+            port: Self::port(),
+            topic: String::new(),
+            username: String::new(),
+            password: String::new(),
+        }
+    }
+}
+
+impl MqttConfig {
+    fn host() -> String {
+        "localhost".to_string()
+    }
+
+    fn port() -> u16 {
+        1883
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct HistoryConfig {
+    #[serde(default)]
+    pub length: HistoryLength,
+    #[serde(default)]
+    pub persist: bool,
+    #[serde(default = "HistoryConfig::temperature_threshold")]
+    pub temperature_threshold: Threshold,
+    #[serde(default = "HistoryConfig::pressure_threshold")]
+    pub pressure_threshold: Threshold,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            length: HistoryLength::default(),
+            persist: false,
+            temperature_threshold: Self::temperature_threshold(),
+            pressure_threshold: Self::pressure_threshold(),
+        }
+    }
+}
+
+impl HistoryConfig {
+    fn temperature_threshold() -> Threshold {
+        Threshold(1.0)
+    }
+
+    fn pressure_threshold() -> Threshold {
+        Threshold(1.0)
+    }
+}
+
+/// How long readings are kept in seconds
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct HistoryLength(pub u32);
+
+impl Default for HistoryLength {
+    fn default() -> Self {
+        Self(3 * 3600)
+    }
+}
+
+impl FromStr for HistoryLength {
+    type Err = ParseIntError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u32::from_str(s).map(Self)
+    }
+}
+
+/// Minimum change over the history length for a trend to be rising or falling
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Threshold(pub f32);
+
+#[derive(Default, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct RecorderConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub format: RecorderFormat,
+    /// Defaults to a file in the data directory if empty
+    #[serde(default)]
+    pub path: String,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, EnumString, Eq, PartialEq, Clone)]
+pub enum RecorderFormat {
+    #[default]
+    Csv,
+    /// Only available if built with the sqlite feature
+    Sqlite,
+}
+
+/// Tags computed by an expression from the values of the built-in tags
+#[derive(Default, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct CustomTags(BTreeMap<String, String>);
+
+impl CustomTags {
+    pub fn get(&self, tag: &str) -> Option<&String> {
+        self.0.get(tag)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.0.iter()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
@@ -151,3 +575,14 @@ impl Default for Icons {
         config.icons
     }
 }
+
+#[test]
+fn format_at() {
+    let config: Config = toml::from_str("format = 'a'\nformats = ['b', 'c']").unwrap();
+    let formats: Vec<_> = (0..3).map(|i| config.format_at(i).0.as_str()).collect();
+    assert_eq!(formats, ["b", "c", "b"]);
+    assert_eq!(config.formats().count(), 2);
+    let config = Config::default();
+    assert_eq!(config.format_at(1), &Format::default());
+    assert_eq!(config.formats().collect::<Vec<_>>(), [&Format::default()]);
+}
@@ -0,0 +1,20 @@
+/// Something that happened while wedder was waiting for the next poll
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Event {
+    /// The config file changed or SIGHUP was received
+    Reload,
+    /// SIGUSR1 asks for polling the weather right away
+    Refresh,
+    /// SIGUSR2 switches to the next of the alternate formats
+    NextFormat,
+    /// The session was locked or suspended
+    Pause,
+    /// The session was unlocked or woke up
+    Resume,
+    /// The wall clock jumped, usually because the machine woke up from suspend
+    ClockJump,
+    /// The internet became reachable
+    Online,
+    /// SIGTERM or SIGINT
+    Exit,
+}
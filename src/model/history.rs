@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::weather::Weather;
+
+/// Snapshot of the values trends are computed for
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Reading {
+    /// Unix timestamp
+    pub time: i64,
+    /// Kelvin
+    pub temperature: Option<f32>,
+    /// Hpa
+    pub pressure: Option<f32>,
+}
+
+impl Reading {
+    pub fn new(time: i64, weather: &dyn Weather) -> Self {
+        Self {
+            time,
+            temperature: weather.temp().map(|temp| temp.0),
+            pressure: weather.pressure().map(|pressure| pressure.0),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Trend {
+    Rising,
+    Falling,
+    Steady,
+}
+
+impl Trend {
+    pub fn new(change: f32, threshold: f32) -> Self {
+        if change >= threshold {
+            Self::Rising
+        } else if change <= -threshold {
+            Self::Falling
+        } else {
+            Self::Steady
+        }
+    }
+
+    pub fn arrow(&self) -> char {
+        match self {
+            Self::Rising => '↑',
+            Self::Falling => '↓',
+            Self::Steady => '→',
+        }
+    }
+}
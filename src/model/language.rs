@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+#[derive(Default, Debug, Serialize, Deserialize, EnumString, Display, Eq, PartialEq, Clone)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Language {
+    #[default]
+    En,
+    De,
+    Fr,
+}
+
+impl Language {
+    /// Translates a snake case key like a weather condition, falling back to English
+    pub fn translate(&self, key: &str) -> String {
+        let lookup = |table: &[(&str, &'static str)]| {
+            table
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, translation)| *translation)
+        };
+        let translation = match self {
+            Self::En => None,
+            Self::De => lookup(DE),
+            Self::Fr => lookup(FR),
+        };
+        translation
+            .or_else(|| lookup(EN))
+            .unwrap_or(key)
+            .to_string()
+    }
+
+    pub fn localize_number(&self, number: &str) -> String {
+        match self {
+            Self::En => number.to_string(),
+            Self::De | Self::Fr => number.replace('.', ","),
+        }
+    }
+
+    pub fn delocalize_number(&self, number: &str) -> String {
+        match self {
+            Self::En => number.to_string(),
+            Self::De | Self::Fr => number.replace(',', "."),
+        }
+    }
+}
+
+const EN: &[(&str, &str)] = &[
+    ("not_available", "N/A"),
+    ("clear_sky", "Clear sky"),
+    ("few_clouds", "Few clouds"),
+    ("clouds", "Clouds"),
+    ("many_clouds", "Many clouds"),
+    ("overcast", "Overcast"),
+    ("drizzle", "Drizzle"),
+    ("light_rain", "Light rain"),
+    ("rain", "Rain"),
+    ("heavy_rain", "Heavy rain"),
+    ("shower_rain", "Shower rain"),
+    ("freezing_rain", "Freezing rain"),
+    ("thunderstorm", "Thunderstorm"),
+    ("light_snow", "Light snow"),
+    ("snow", "Snow"),
+    ("heavy_snow", "Heavy snow"),
+    ("shower_snow", "Shower snow"),
+    ("sleet", "Sleet"),
+    ("rain_and_snow", "Rain and snow"),
+    ("hail", "Hail"),
+    ("mist", "Mist"),
+    ("fog", "Fog"),
+    ("haze", "Haze"),
+    ("smoke", "Smoke"),
+    ("dust", "Dust"),
+    ("sand", "Sand"),
+    ("volcanic_ash", "Volcanic ash"),
+    ("squalls", "Squalls"),
+    ("tornado", "Tornado"),
+    ("new_moon", "New moon"),
+    ("waxing_crescent", "Waxing crescent"),
+    ("first_quarter", "First quarter"),
+    ("waxing_gibbous", "Waxing gibbous"),
+    ("full_moon", "Full moon"),
+    ("waning_gibbous", "Waning gibbous"),
+    ("last_quarter", "Last quarter"),
+    ("waning_crescent", "Waning crescent"),
+    ("north", "N"),
+    ("north_east", "NE"),
+    ("east", "E"),
+    ("south_east", "SE"),
+    ("south", "S"),
+    ("south_west", "SW"),
+    ("west", "W"),
+    ("north_west", "NW"),
+    ("good", "Good"),
+    ("fair", "Fair"),
+    ("moderate", "Moderate"),
+    ("poor", "Poor"),
+    ("very_poor", "Very poor"),
+    (
+        "unhealthy_for_sensitive_groups",
+        "Unhealthy for sensitive groups",
+    ),
+    ("unhealthy", "Unhealthy"),
+    ("very_unhealthy", "Very unhealthy"),
+    ("hazardous", "Hazardous"),
+    ("very_low", "Very low"),
+    ("low", "Low"),
+    ("medium", "Medium"),
+    ("high", "High"),
+    ("very_high", "Very high"),
+    ("no_pollen", "None"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("not_available", "k. A."),
+    ("clear_sky", "Klarer Himmel"),
+    ("few_clouds", "Leicht bewölkt"),
+    ("clouds", "Bewölkt"),
+    ("many_clouds", "Stark bewölkt"),
+    ("overcast", "Bedeckt"),
+    ("drizzle", "Nieselregen"),
+    ("light_rain", "Leichter Regen"),
+    ("rain", "Regen"),
+    ("heavy_rain", "Starker Regen"),
+    ("shower_rain", "Regenschauer"),
+    ("freezing_rain", "Eisregen"),
+    ("thunderstorm", "Gewitter"),
+    ("light_snow", "Leichter Schneefall"),
+    ("snow", "Schnee"),
+    ("heavy_snow", "Starker Schneefall"),
+    ("shower_snow", "Schneeschauer"),
+    ("sleet", "Schneeregen"),
+    ("rain_and_snow", "Regen und Schnee"),
+    ("hail", "Hagel"),
+    ("mist", "Dunst"),
+    ("fog", "Nebel"),
+    ("haze", "Diesig"),
+    ("smoke", "Rauch"),
+    ("dust", "Staub"),
+    ("sand", "Sand"),
+    ("volcanic_ash", "Vulkanasche"),
+    ("squalls", "Sturmböen"),
+    ("tornado", "Tornado"),
+    ("new_moon", "Neumond"),
+    ("waxing_crescent", "Zunehmende Sichel"),
+    ("first_quarter", "Erstes Viertel"),
+    ("waxing_gibbous", "Zunehmender Mond"),
+    ("full_moon", "Vollmond"),
+    ("waning_gibbous", "Abnehmender Mond"),
+    ("last_quarter", "Letztes Viertel"),
+    ("waning_crescent", "Abnehmende Sichel"),
+    ("north", "N"),
+    ("north_east", "NO"),
+    ("east", "O"),
+    ("south_east", "SO"),
+    ("south", "S"),
+    ("south_west", "SW"),
+    ("west", "W"),
+    ("north_west", "NW"),
+    ("good", "Gut"),
+    ("fair", "Ausreichend"),
+    ("moderate", "Mäßig"),
+    ("poor", "Schlecht"),
+    ("very_poor", "Sehr schlecht"),
+    (
+        "unhealthy_for_sensitive_groups",
+        "Ungesund für empfindliche Gruppen",
+    ),
+    ("unhealthy", "Ungesund"),
+    ("very_unhealthy", "Sehr ungesund"),
+    ("hazardous", "Gefährlich"),
+    ("very_low", "Sehr niedrig"),
+    ("low", "Niedrig"),
+    ("medium", "Mittel"),
+    ("high", "Hoch"),
+    ("very_high", "Sehr hoch"),
+    ("no_pollen", "Keine"),
+];
+
+const FR: &[(&str, &str)] = &[
+    ("not_available", "N/D"),
+    ("clear_sky", "Ciel dégagé"),
+    ("few_clouds", "Peu nuageux"),
+    ("clouds", "Nuageux"),
+    ("many_clouds", "Très nuageux"),
+    ("overcast", "Couvert"),
+    ("drizzle", "Bruine"),
+    ("light_rain", "Pluie légère"),
+    ("rain", "Pluie"),
+    ("heavy_rain", "Forte pluie"),
+    ("shower_rain", "Averses"),
+    ("freezing_rain", "Pluie verglaçante"),
+    ("thunderstorm", "Orage"),
+    ("light_snow", "Neige légère"),
+    ("snow", "Neige"),
+    ("heavy_snow", "Fortes chutes de neige"),
+    ("shower_snow", "Averses de neige"),
+    ("sleet", "Neige fondue"),
+    ("rain_and_snow", "Pluie et neige"),
+    ("hail", "Grêle"),
+    ("mist", "Brume"),
+    ("fog", "Brouillard"),
+    ("haze", "Brume sèche"),
+    ("smoke", "Fumée"),
+    ("dust", "Poussière"),
+    ("sand", "Sable"),
+    ("volcanic_ash", "Cendres volcaniques"),
+    ("squalls", "Grains"),
+    ("tornado", "Tornade"),
+    ("new_moon", "Nouvelle lune"),
+    ("waxing_crescent", "Premier croissant"),
+    ("first_quarter", "Premier quartier"),
+    ("waxing_gibbous", "Gibbeuse croissante"),
+    ("full_moon", "Pleine lune"),
+    ("waning_gibbous", "Gibbeuse décroissante"),
+    ("last_quarter", "Dernier quartier"),
+    ("waning_crescent", "Dernier croissant"),
+    ("north", "N"),
+    ("north_east", "NE"),
+    ("east", "E"),
+    ("south_east", "SE"),
+    ("south", "S"),
+    ("south_west", "SO"),
+    ("west", "O"),
+    ("north_west", "NO"),
+    ("good", "Bon"),
+    ("fair", "Correct"),
+    ("moderate", "Modéré"),
+    ("poor", "Mauvais"),
+    ("very_poor", "Très mauvais"),
+    (
+        "unhealthy_for_sensitive_groups",
+        "Mauvais pour les groupes sensibles",
+    ),
+    ("unhealthy", "Mauvais pour la santé"),
+    ("very_unhealthy", "Très mauvais pour la santé"),
+    ("hazardous", "Dangereux"),
+    ("very_low", "Très faible"),
+    ("low", "Faible"),
+    ("medium", "Moyen"),
+    ("high", "Élevé"),
+    ("very_high", "Très élevé"),
+    ("no_pollen", "Aucun"),
+];
+
+#[test]
+fn translate() {
+    assert_eq!(Language::En.translate("full_moon"), "Full moon");
+    assert_eq!(Language::De.translate("full_moon"), "Vollmond");
+    assert_eq!(Language::Fr.translate("not_available"), "N/D");
+    assert_eq!(Language::De.translate("unknown"), "unknown");
+    assert_eq!(Language::De.localize_number("-2.5"), "-2,5");
+}
@@ -14,6 +14,20 @@ pub struct Location {
     pub lon: f32,
 }
 
+impl Location {
+    const EARTH_RADIUS: f32 = 6_371_000.0;
+
+    /// Great-circle distance in meters
+    pub fn distance(&self, other: &Location) -> f32 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.lon - self.lon).to_radians();
+
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * Self::EARTH_RADIUS * a.sqrt().asin()
+    }
+}
+
 impl PartialEq for Location {
     fn eq(&self, other: &Self) -> bool {
         self.city == other.city
@@ -28,6 +42,28 @@ impl Eq for Location {}
 pub enum LocationProvider {
     IpApi,
     Manual,
+    Network,
+    Command,
+}
+
+/// A location identified by the WiFi or the gateway the machine is connected to
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NetworkLocation {
+    pub ssid: Option<String>,
+    pub gateway_mac: Option<String>,
+    #[serde(flatten)]
+    pub location: Location,
+}
+
+impl NetworkLocation {
+    pub fn matches(&self, ssid: Option<&str>, gateway_mac: Option<&str>) -> bool {
+        let ssid_matches = matches!((&self.ssid, ssid), (Some(a), Some(b)) if a == b);
+        let mac_matches = matches!(
+            (&self.gateway_mac, gateway_mac),
+            (Some(a), Some(b)) if a.eq_ignore_ascii_case(b)
+        );
+        ssid_matches || mac_matches
+    }
 }
 
 impl Default for LocationProvider {
@@ -35,3 +71,35 @@ impl Default for LocationProvider {
         Self::IpApi
     }
 }
+
+#[test]
+fn distance() {
+    let london = Location {
+        city: None,
+        lat: 51.5074,
+        lon: -0.1278,
+    };
+    let paris = Location {
+        city: None,
+        lat: 48.8566,
+        lon: 2.3522,
+    };
+
+    assert_eq!(london.distance(&london), 0.0);
+    assert_eq!((london.distance(&paris) / 1000.0).round(), 344.0);
+    assert_eq!(london.distance(&paris), paris.distance(&london));
+}
+
+#[test]
+fn network_matches() {
+    let network = NetworkLocation {
+        ssid: Some("Home".to_string()),
+        gateway_mac: Some("AA:BB:CC:DD:EE:FF".to_string()),
+        location: Location::default(),
+    };
+
+    assert!(network.matches(Some("Home"), None));
+    assert!(network.matches(None, Some("aa:bb:cc:dd:ee:ff")));
+    assert!(!network.matches(Some("Office"), Some("11:22:33:44:55:66")));
+    assert!(!network.matches(None, None));
+}
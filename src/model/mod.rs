@@ -1,3 +1,12 @@
+pub mod air_quality;
+pub mod astronomy;
+pub mod comfort;
 pub mod config;
+pub mod event;
+pub mod history;
+pub mod language;
 pub mod location;
+pub mod pollen;
+pub mod record;
+pub mod sensor;
 pub mod weather;
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use strum_macros::{Display, EnumString};
+
+use crate::model::location::Location;
+
+/// Tags requiring pollen data
+pub const POLLEN_TAGS: [&str; 7] = [
+    "alder_pollen",
+    "birch_pollen",
+    "grass_pollen",
+    "mugwort_pollen",
+    "olive_pollen",
+    "ragweed_pollen",
+    "pollen_level",
+];
+
+#[derive(Default, Debug, Serialize, Deserialize, EnumString, Eq, PartialEq, Clone)]
+pub enum PollenProvider {
+    #[default]
+    OpenMeteo,
+}
+
+pub trait CurrentPollen {
+    fn pollen(&self, location: &Location) -> crate::Result<Box<dyn Pollen>>;
+}
+
+pub trait Pollen {
+    fn alder(&self) -> Option<Grains>;
+    fn birch(&self) -> Option<Grains>;
+    fn grass(&self) -> Option<Grains>;
+    fn mugwort(&self) -> Option<Grains>;
+    fn olive(&self) -> Option<Grains>;
+    fn ragweed(&self) -> Option<Grains>;
+
+    /// The highest level of all species, none if no species is available
+    fn level(&self) -> Option<PollenLevel> {
+        [
+            self.alder().map(|grains| grains.level(&TREES)),
+            self.birch().map(|grains| grains.level(&TREES)),
+            self.olive().map(|grains| grains.level(&TREES)),
+            self.grass().map(|grains| grains.level(&GRASS)),
+            self.mugwort().map(|grains| grains.level(&WEEDS)),
+            self.ragweed().map(|grains| grains.level(&WEEDS)),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+    }
+}
+
+/// Lower bounds of the low, moderate, high and very high levels in grains/m³
+/// following the scale of the National Allergy Bureau
+const TREES: [f32; 4] = [1.0, 15.0, 90.0, 1500.0];
+const GRASS: [f32; 4] = [1.0, 5.0, 20.0, 200.0];
+const WEEDS: [f32; 4] = [1.0, 10.0, 50.0, 500.0];
+
+/// Pollen grains/m³
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq)]
+pub struct Grains(pub f32);
+
+impl Grains {
+    fn level(&self, thresholds: &[f32; 4]) -> PollenLevel {
+        let levels = [
+            PollenLevel::Low,
+            PollenLevel::Moderate,
+            PollenLevel::High,
+            PollenLevel::VeryHigh,
+        ];
+        thresholds
+            .iter()
+            .zip(levels)
+            .filter(|(threshold, _)| self.0 >= **threshold)
+            .map(|(_, level)| level)
+            .next_back()
+            .unwrap_or(PollenLevel::NoPollen)
+    }
+}
+
+impl Display for Grains {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.0}", self.0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum PollenLevel {
+    NoPollen,
+    Low,
+    Moderate,
+    High,
+    VeryHigh,
+}
+
+#[test]
+fn level() {
+    assert_eq!(Grains(0.0).level(&TREES), PollenLevel::NoPollen);
+    assert_eq!(Grains(14.9).level(&TREES), PollenLevel::Low);
+    assert_eq!(Grains(20.0).level(&TREES), PollenLevel::Moderate);
+    assert_eq!(Grains(20.0).level(&GRASS), PollenLevel::High);
+    assert_eq!(Grains(600.0).level(&WEEDS), PollenLevel::VeryHigh);
+}
@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::location::Location;
+use crate::model::pollen::Pollen;
+use crate::model::weather::Weather;
+
+pub trait Recorder {
+    fn record(&mut self, record: &Record) -> crate::Result<()>;
+    /// Records from the start timestamp up to but excluding the end timestamp
+    fn records(&self, start: i64, end: i64) -> crate::Result<Vec<Record>>;
+}
+
+/// Snapshot of a poll with all values in SI units
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Record {
+    /// Unix timestamp
+    pub time: i64,
+    pub city: Option<String>,
+    pub lat: f32,
+    pub lon: f32,
+    pub condition: Option<String>,
+    /// Kelvin
+    pub temperature: Option<f32>,
+    /// Kelvin
+    pub temperature_feels_like: Option<f32>,
+    /// Kelvin
+    pub temperature_max: Option<f32>,
+    /// Kelvin
+    pub temperature_min: Option<f32>,
+    /// Kelvin
+    pub dew_point: Option<f32>,
+    /// Millimeter
+    pub precipitation: Option<f32>,
+    /// Percentage
+    pub precipitation_chance: Option<f32>,
+    /// Percentage
+    pub clouds: Option<f32>,
+    /// Percentage
+    pub humidity: Option<f32>,
+    /// Meter
+    pub visibility: Option<f32>,
+    /// m/s
+    pub wind_speed: Option<f32>,
+    /// m/s
+    pub wind_gust: Option<f32>,
+    /// Degree
+    pub wind_direction: Option<f32>,
+    /// Hpa
+    pub pressure: Option<f32>,
+    pub uv_index: Option<f32>,
+    pub air_quality_index: Option<f32>,
+    /// µg/m³
+    pub pm2_5: Option<f32>,
+    /// µg/m³
+    pub pm10: Option<f32>,
+    /// µg/m³
+    pub o3: Option<f32>,
+    /// µg/m³
+    pub no2: Option<f32>,
+    /// µg/m³
+    pub so2: Option<f32>,
+    /// µg/m³
+    pub co: Option<f32>,
+    /// µg/m³
+    pub nh3: Option<f32>,
+    /// Grains/m³
+    pub alder_pollen: Option<f32>,
+    /// Grains/m³
+    pub birch_pollen: Option<f32>,
+    /// Grains/m³
+    pub grass_pollen: Option<f32>,
+    /// Grains/m³
+    pub mugwort_pollen: Option<f32>,
+    /// Grains/m³
+    pub olive_pollen: Option<f32>,
+    /// Grains/m³
+    pub ragweed_pollen: Option<f32>,
+    /// Kelvin
+    pub heat_index: Option<f32>,
+    /// Kelvin
+    pub wind_chill: Option<f32>,
+    /// Kelvin
+    pub humidex: Option<f32>,
+    /// g/m³
+    pub absolute_humidity: Option<f32>,
+}
+
+impl Record {
+    pub fn new(
+        time: i64,
+        location: &Location,
+        weather: &dyn Weather,
+        pollen: Option<&dyn Pollen>,
+    ) -> Self {
+        let pollutants = weather.pollutants().unwrap_or_default();
+        Self {
+            time,
+            city: location.city.clone(),
+            lat: location.lat,
+            lon: location.lon,
+            condition: weather
+                .weather_condition()
+                .map(|condition| condition.to_string()),
+            temperature: weather.temp().map(|t| t.0),
+            temperature_feels_like: weather.temp_feels_like().map(|t| t.0),
+            temperature_max: weather.temp_max().map(|t| t.0),
+            temperature_min: weather.temp_min().map(|t| t.0),
+            dew_point: weather.dew_point().map(|t| t.0),
+            precipitation: weather.precipitation().map(|p| p.0),
+            precipitation_chance: weather.precipitation_chance().map(|p| p.0),
+            clouds: weather.clouds().map(|p| p.0),
+            humidity: weather.humidity().map(|p| p.0),
+            visibility: weather.visibility().map(|m| m.0),
+            wind_speed: weather.wind_speed().map(|ms| ms.0),
+            wind_gust: weather.wind_gust().map(|ms| ms.0),
+            wind_direction: weather.wind_direction().map(|degree| degree.0),
+            pressure: weather.pressure().map(|hpa| hpa.0),
+            uv_index: weather.uvi().map(|uvi| uvi.0),
+            air_quality_index: weather.aqi().map(|aqi| aqi.0),
+            pm2_5: pollutants.pm2_5.map(|c| c.0),
+            pm10: pollutants.pm10.map(|c| c.0),
+            o3: pollutants.o3.map(|c| c.0),
+            no2: pollutants.no2.map(|c| c.0),
+            so2: pollutants.so2.map(|c| c.0),
+            co: pollutants.co.map(|c| c.0),
+            nh3: pollutants.nh3.map(|c| c.0),
+            alder_pollen: pollen.and_then(|pollen| pollen.alder()).map(|g| g.0),
+            birch_pollen: pollen.and_then(|pollen| pollen.birch()).map(|g| g.0),
+            grass_pollen: pollen.and_then(|pollen| pollen.grass()).map(|g| g.0),
+            mugwort_pollen: pollen.and_then(|pollen| pollen.mugwort()).map(|g| g.0),
+            olive_pollen: pollen.and_then(|pollen| pollen.olive()).map(|g| g.0),
+            ragweed_pollen: pollen.and_then(|pollen| pollen.ragweed()).map(|g| g.0),
+            heat_index: weather.heat_index().map(|t| t.0),
+            wind_chill: weather.wind_chill().map(|t| t.0),
+            humidex: weather.humidex().map(|t| t.0),
+            absolute_humidity: weather.absolute_humidity().map(|h| h.0),
+        }
+    }
+
+    /// The names of all fields in the order they are stored in
+    pub fn columns() -> Vec<&'static str> {
+        let values = Self::default().values().map(|(name, _)| name);
+        ["time", "city", "lat", "lon", "condition"]
+            .into_iter()
+            .chain(values)
+            .collect()
+    }
+
+    /// The numeric weather values by their name
+    pub fn values(&self) -> [(&'static str, Option<f32>); 33] {
+        self.clone()
+            .values_mut()
+            .map(|(name, value)| (name, *value))
+    }
+
+    /// The numeric weather values by their name, in the order of the fields
+    pub fn values_mut(&mut self) -> [(&'static str, &mut Option<f32>); 33] {
+        [
+            ("temperature", &mut self.temperature),
+            ("temperature_feels_like", &mut self.temperature_feels_like),
+            ("temperature_max", &mut self.temperature_max),
+            ("temperature_min", &mut self.temperature_min),
+            ("dew_point", &mut self.dew_point),
+            ("precipitation", &mut self.precipitation),
+            ("precipitation_chance", &mut self.precipitation_chance),
+            ("clouds", &mut self.clouds),
+            ("humidity", &mut self.humidity),
+            ("visibility", &mut self.visibility),
+            ("wind_speed", &mut self.wind_speed),
+            ("wind_gust", &mut self.wind_gust),
+            ("wind_direction", &mut self.wind_direction),
+            ("pressure", &mut self.pressure),
+            ("uv_index", &mut self.uv_index),
+            ("air_quality_index", &mut self.air_quality_index),
+            ("pm2_5", &mut self.pm2_5),
+            ("pm10", &mut self.pm10),
+            ("o3", &mut self.o3),
+            ("no2", &mut self.no2),
+            ("so2", &mut self.so2),
+            ("co", &mut self.co),
+            ("nh3", &mut self.nh3),
+            ("alder_pollen", &mut self.alder_pollen),
+            ("birch_pollen", &mut self.birch_pollen),
+            ("grass_pollen", &mut self.grass_pollen),
+            ("mugwort_pollen", &mut self.mugwort_pollen),
+            ("olive_pollen", &mut self.olive_pollen),
+            ("ragweed_pollen", &mut self.ragweed_pollen),
+            ("heat_index", &mut self.heat_index),
+            ("wind_chill", &mut self.wind_chill),
+            ("humidex", &mut self.humidex),
+            ("absolute_humidity", &mut self.absolute_humidity),
+        ]
+    }
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use strum_macros::EnumString;
+
+use crate::model::config::TemperatureUnit;
+use crate::model::weather::{Kelvin, Percentage};
+
+/// Tags requiring a sensor reading
+pub const SENSOR_TAGS: [&str; 2] = ["indoor_temperature", "indoor_humidity"];
+
+/// A local source of indoor readings
+pub trait Sensor {
+    fn reading(&self) -> crate::Result<SensorReading>;
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, EnumString, Eq, PartialEq, Clone)]
+pub enum SensorProvider {
+    #[default]
+    Disabled,
+    File,
+    Command,
+    Mqtt,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct SensorReading {
+    pub temperature: Option<Kelvin>,
+    pub humidity: Option<Percentage>,
+}
+
+impl SensorReading {
+    /// Parses the output of a DS18B20 w1_slave file, a JSON object with temperature and
+    /// humidity keys, or a temperature optionally followed by the humidity
+    pub fn parse(text: &str, unit: &TemperatureUnit) -> crate::Result<Self> {
+        let text = text.trim();
+        if text.contains("crc=") && !text.contains("YES") {
+            return Err("Sensor reading failed the CRC check".into());
+        }
+        let (temperature, humidity) = if let Some((_, millis)) = text.rsplit_once("t=") {
+            let millis: f32 = millis.trim().parse()?;
+            (
+                Some(Kelvin::from_unit(
+                    millis / 1000.0,
+                    &TemperatureUnit::Celsius,
+                )),
+                None,
+            )
+        } else if text.starts_with('{') {
+            let json: Value = serde_json::from_str(text)?;
+            let number = |key| json.get(key).and_then(Value::as_f64).map(|n| n as f32);
+            (
+                number("temperature").map(|t| Kelvin::from_unit(t, unit)),
+                number("humidity"),
+            )
+        } else {
+            let mut numbers = text.split_whitespace().map(str::parse::<f32>);
+            let temperature = numbers
+                .next()
+                .transpose()?
+                .map(|t| Kelvin::from_unit(t, unit));
+            (temperature, numbers.next().transpose()?)
+        };
+
+        if temperature.is_none() && humidity.is_none() {
+            return Err(format!("Couldn't parse sensor reading '{}'", text).into());
+        }
+        Ok(Self {
+            temperature,
+            humidity: humidity.map(Percentage),
+        })
+    }
+}
+
+#[test]
+fn parse() {
+    let celsius = &TemperatureUnit::Celsius;
+    let w1_slave = "72 01 4b 46 7f ff 0e 10 57 : crc=57 YES\n72 01 4b 46 7f ff 0e 10 57 t=23125\n";
+    let reading = SensorReading::parse(w1_slave, celsius).unwrap();
+    assert_eq!(reading.temperature, Some(Kelvin(23.125 + 273.15)));
+    assert_eq!(reading.humidity, None);
+
+    let json = r#"{"temperature": 70, "humidity": 40.5, "battery": 90}"#;
+    let reading = SensorReading::parse(json, &TemperatureUnit::Fahrenheit).unwrap();
+    assert!((reading.temperature.unwrap().0 - 294.26).abs() < 0.01);
+    assert_eq!(reading.humidity, Some(Percentage(40.5)));
+
+    let reading = SensorReading::parse("21.5 40\n", celsius).unwrap();
+    assert_eq!(reading.temperature, Some(Kelvin(21.5 + 273.15)));
+    assert_eq!(reading.humidity, Some(Percentage(40.0)));
+
+    let crc_error = "72 01 4b 46 7f ff 0e 10 57 : crc=12 NO\n72 01 4b 46 7f ff 0e 10 57 t=23125\n";
+    assert!(SensorReading::parse(crc_error, celsius).is_err());
+    assert!(SensorReading::parse("", celsius).is_err());
+    assert!(SensorReading::parse("warm", celsius).is_err());
+}
@@ -1,28 +1,60 @@
-use chrono::{DateTime, Local};
+use chrono::{DateTime, FixedOffset, Utc};
 use serde::{Deserialize, Serialize};
 use strum_macros::EnumString;
 
+use crate::model::air_quality::Pollutants;
+use crate::model::comfort;
+use crate::model::comfort::AbsoluteHumidity;
 use crate::model::config::{PrecipitationUnit, WindSpeedUnit};
 use crate::model::location::Location;
+use std::collections::HashSet;
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
 
 use strum_macros::Display;
 
 use super::config::{DistanceUnit, TemperatureUnit};
+use super::language::Language;
 
 pub trait CurrentWeather {
-    fn weather(&self, location: &Location, api_key: &str) -> crate::Result<Box<dyn Weather>>;
+    /// Providers may skip querying data that none of the used tags displays
+    fn weather(
+        &self,
+        location: &Location,
+        tags: &HashSet<String>,
+    ) -> crate::Result<Box<dyn Weather>>;
+    /// How often the provider publishes new data, aligned to the full hour
+    fn update_period(&self) -> Option<Duration> {
+        None
+    }
 }
 
 pub trait Weather {
     fn weather_condition(&self) -> Option<WeatherCondition>;
+    /// Description of the weather condition as provided by the weather provider
+    fn description(&self) -> Option<String>;
 
     fn temp(&self) -> Option<Kelvin>;
     fn temp_feels_like(&self) -> Option<Kelvin>;
     fn temp_max(&self) -> Option<Kelvin>;
     fn temp_min(&self) -> Option<Kelvin>;
-    fn dew_point(&self) -> Option<Kelvin>;
+    /// Computed from temperature and humidity by default
+    fn dew_point(&self) -> Option<Kelvin> {
+        comfort::dew_point(self.temp()?, self.humidity()?)
+    }
+    fn heat_index(&self) -> Option<Kelvin> {
+        comfort::heat_index(self.temp()?, self.humidity()?)
+    }
+    fn wind_chill(&self) -> Option<Kelvin> {
+        comfort::wind_chill(self.temp()?, self.wind_speed()?)
+    }
+    fn humidex(&self) -> Option<Kelvin> {
+        comfort::humidex(self.temp()?, self.dew_point()?)
+    }
+    fn absolute_humidity(&self) -> Option<AbsoluteHumidity> {
+        Some(comfort::absolute_humidity(self.temp()?, self.humidity()?))
+    }
 
     fn precipitation(&self) -> Option<Millimeter>;
     fn precipitation_chance(&self) -> Option<Percentage>;
@@ -30,34 +62,130 @@ pub trait Weather {
     fn humidity(&self) -> Option<Percentage>;
     fn visibility(&self) -> Option<Meter>;
     fn wind_speed(&self) -> Option<Ms>;
+    fn wind_gust(&self) -> Option<Ms>;
+    /// The direction the wind is coming from
+    fn wind_direction(&self) -> Option<Degree>;
     fn pressure(&self) -> Option<Hpa>;
     fn uvi(&self) -> Option<Uvi>;
     fn aqi(&self) -> Option<Aqi>;
+    fn pollutants(&self) -> Option<Pollutants>;
+
+    fn sunrise(&self) -> Option<DateTime<Utc>>;
+    fn sunset(&self) -> Option<DateTime<Utc>>;
+    /// Whether the sun is up at the location, derived from sunrise and sunset by default
+    fn is_day(&self) -> Option<bool> {
+        let now = Utc::now();
+        Some(self.sunrise()? <= now && now < self.sunset()?)
+    }
+    /// Offset of the location's local time to UTC
+    fn utc_offset(&self) -> Option<FixedOffset>;
+    /// When the provider observed the weather, which is when it was fetched for live data
+    fn observed_at(&self) -> Option<DateTime<Utc>>;
+    /// What the provider's responses tell about when to request again
+    fn freshness(&self) -> Freshness {
+        Freshness::default()
+    }
 
-    fn sunrise(&self) -> Option<DateTime<Local>>;
-    fn sunset(&self) -> Option<DateTime<Local>>;
+    /// Whether a weather alert is in effect for the location
+    fn has_alert(&self) -> bool {
+        false
+    }
+
+    /// Whether it rains, snows, storms or an alert is in effect, which makes the weather
+    /// change quickly
+    fn is_eventful(&self) -> bool {
+        let precipitation = self.precipitation().is_some_and(|mm| mm.0 > 0.0);
+        let condition = self.weather_condition();
+        precipitation
+            || condition.is_some_and(|c| c.is_precipitation() || c.is_severe())
+            || self.has_alert()
+    }
+}
+
+/// When a provider's data is worth requesting again
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct Freshness {
+    /// When the first of the responses may no longer be cached
+    pub expires: Option<DateTime<Utc>>,
+    /// When a rate limited provider accepts requests again
+    pub retry_after: Option<DateTime<Utc>>,
+}
+
+impl Freshness {
+    /// Combines the freshness of several responses
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            expires: self.expires.into_iter().chain(other.expires).min(),
+            retry_after: self.retry_after.into_iter().chain(other.retry_after).max(),
+        }
+    }
 }
 
 pub trait Convert {
     type Unit;
     fn convert(&self, unit: &Self::Unit) -> String;
+
+    fn convert_localized(&self, unit: &Self::Unit, language: &Language) -> String {
+        language.localize_number(&self.convert(unit))
+    }
 }
 
 #[derive(Debug, Deserialize, Copy, Clone, PartialEq)]
 pub struct Kelvin(pub f32);
 
+impl Kelvin {
+    pub fn from_unit(value: f32, unit: &TemperatureUnit) -> Self {
+        match unit {
+            TemperatureUnit::Celsius => Self::from_celsius(value),
+            TemperatureUnit::Fahrenheit => Self::from_fahrenheit(value),
+            TemperatureUnit::Kelvin => Self(value),
+        }
+    }
+
+    pub fn from_celsius(celsius: f32) -> Self {
+        Self(celsius + 273.15)
+    }
+
+    pub fn from_fahrenheit(fahrenheit: f32) -> Self {
+        Self::from_celsius((fahrenheit - 32.0) * (5.0 / 9.0))
+    }
+
+    pub fn celsius(&self) -> f32 {
+        self.0 - 273.15
+    }
+
+    pub fn fahrenheit(&self) -> f32 {
+        self.celsius() * (9.0 / 5.0) + 32.0
+    }
+}
+
 impl Convert for Kelvin {
     type Unit = TemperatureUnit;
     fn convert(&self, unit: &Self::Unit) -> String {
         let converted = match unit {
-            TemperatureUnit::Celsius => self.0 - 273.15,
-            TemperatureUnit::Fahrenheit => (self.0 - 273.15) * (9.0 / 5.0) + 32.0,
+            TemperatureUnit::Celsius => self.celsius(),
+            TemperatureUnit::Fahrenheit => self.fahrenheit(),
             TemperatureUnit::Kelvin => self.0,
         };
         format!("{:.0}", converted)
     }
 }
 
+/// Difference between two temperatures
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct KelvinDelta(pub f32);
+
+impl Convert for KelvinDelta {
+    type Unit = TemperatureUnit;
+    fn convert(&self, unit: &Self::Unit) -> String {
+        let converted = match unit {
+            TemperatureUnit::Celsius | TemperatureUnit::Kelvin => self.0,
+            TemperatureUnit::Fahrenheit => self.0 * (9.0 / 5.0),
+        };
+        format!("{:+.1}", converted)
+    }
+}
+
 #[derive(Debug, Deserialize, Copy, Clone)]
 pub struct Millimeter(pub f32);
 
@@ -99,6 +227,24 @@ impl Convert for Meter {
 #[derive(Debug, Deserialize, Copy, Clone)]
 pub struct Ms(pub f32);
 
+impl Ms {
+    pub fn from_kmh(kmh: f32) -> Self {
+        Self(kmh / 3.6)
+    }
+
+    /// Upper limits of the Beaufort scale in m/s
+    const BEAUFORT: [f32; 12] = [
+        0.5, 1.6, 3.4, 5.5, 8.0, 10.8, 13.9, 17.2, 20.8, 24.5, 28.5, 32.7,
+    ];
+
+    fn beaufort(&self) -> usize {
+        Self::BEAUFORT
+            .iter()
+            .position(|limit| self.0 < *limit)
+            .unwrap_or(Self::BEAUFORT.len())
+    }
+}
+
 impl Convert for Ms {
     type Unit = WindSpeedUnit;
     fn convert(&self, unit: &Self::Unit) -> String {
@@ -106,11 +252,49 @@ impl Convert for Ms {
             WindSpeedUnit::Ms => self.0,
             WindSpeedUnit::Kmh => self.0 * 3.6,
             WindSpeedUnit::Mph => self.0 * (3600.0 / 1609.34),
+            WindSpeedUnit::Beaufort => return self.beaufort().to_string(),
         };
         format!("{:.1}", converted)
     }
 }
 
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq)]
+pub struct Degree(pub f32);
+
+impl Degree {
+    const CARDINALS: [&'static str; 8] = [
+        "north",
+        "north_east",
+        "east",
+        "south_east",
+        "south",
+        "south_west",
+        "west",
+        "north_west",
+    ];
+    const ARROWS: [char; 8] = ['↑', '↗', '→', '↘', '↓', '↙', '←', '↖'];
+
+    fn octant(&self) -> usize {
+        (self.0.rem_euclid(360.0) / 45.0).round() as usize % 8
+    }
+
+    /// Name of the nearest of the eight cardinal and intercardinal directions
+    pub fn cardinal(&self) -> &'static str {
+        Self::CARDINALS[self.octant()]
+    }
+
+    /// Arrow pointing in the opposite direction, i.e. where the wind blows to
+    pub fn arrow(&self) -> char {
+        Self::ARROWS[(self.octant() + 4) % 8]
+    }
+}
+
+impl Display for Degree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.0}", self.0)
+    }
+}
+
 #[derive(Debug, Deserialize, Copy, Clone)]
 pub struct Hpa(pub f32);
 
@@ -141,6 +325,10 @@ impl Display for Aqi {
 #[derive(Debug, Serialize, Deserialize, EnumString, Eq, PartialEq, Clone)]
 pub enum WeatherProvider {
     OpenWeatherMap,
+    WeatherUnderground,
+    Netatmo,
+    Ecowitt,
+    Command,
 }
 
 impl Default for WeatherProvider {
@@ -149,16 +337,122 @@ impl Default for WeatherProvider {
     }
 }
 
-#[derive(Debug, Hash, Eq, PartialEq, Serialize, Deserialize, Display)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
 #[strum(serialize_all = "snake_case")]
 pub enum WeatherCondition {
     ClearSky,
     FewClouds,
     Clouds,
     ManyClouds,
+    Overcast,
+    Drizzle,
+    LightRain,
     Rain,
     HeavyRain,
+    ShowerRain,
+    FreezingRain,
     Thunderstorm,
+    LightSnow,
     Snow,
+    HeavySnow,
+    ShowerSnow,
+    Sleet,
+    RainAndSnow,
+    Hail,
     Mist,
+    Fog,
+    Haze,
+    Smoke,
+    Dust,
+    Sand,
+    VolcanicAsh,
+    Squalls,
+    Tornado,
+}
+
+impl WeatherCondition {
+    pub fn is_precipitation(&self) -> bool {
+        matches!(
+            self,
+            Self::Drizzle
+                | Self::LightRain
+                | Self::Rain
+                | Self::HeavyRain
+                | Self::ShowerRain
+                | Self::FreezingRain
+                | Self::LightSnow
+                | Self::Snow
+                | Self::HeavySnow
+                | Self::ShowerSnow
+                | Self::Sleet
+                | Self::RainAndSnow
+                | Self::Hail
+        )
+    }
+
+    /// Conditions a weather alert would be issued for
+    pub fn is_severe(&self) -> bool {
+        matches!(
+            self,
+            Self::Thunderstorm | Self::Hail | Self::Squalls | Self::Tornado | Self::VolcanicAsh
+        )
+    }
+
+    /// The coarser condition whose icon is displayed if there is no icon for this condition
+    ///
+    /// Every condition eventually falls back to one of
+    /// clear_sky, few_clouds, clouds, many_clouds, rain, heavy_rain, thunderstorm, snow or mist
+    pub fn fallback(&self) -> Option<Self> {
+        match self {
+            Self::Overcast => Some(Self::ManyClouds),
+            Self::Drizzle => Some(Self::LightRain),
+            Self::LightRain | Self::ShowerRain => Some(Self::Rain),
+            Self::FreezingRain | Self::RainAndSnow | Self::Hail => Some(Self::Sleet),
+            Self::LightSnow | Self::HeavySnow | Self::ShowerSnow | Self::Sleet => Some(Self::Snow),
+            Self::Fog | Self::Haze => Some(Self::Mist),
+            Self::Smoke | Self::Dust => Some(Self::Haze),
+            Self::Sand => Some(Self::Dust),
+            Self::VolcanicAsh => Some(Self::Smoke),
+            Self::Tornado => Some(Self::Squalls),
+            Self::Squalls => Some(Self::Thunderstorm),
+            Self::ClearSky
+            | Self::FewClouds
+            | Self::Clouds
+            | Self::ManyClouds
+            | Self::Rain
+            | Self::HeavyRain
+            | Self::Thunderstorm
+            | Self::Snow
+            | Self::Mist => None,
+        }
+    }
+}
+
+#[test]
+fn wind() {
+    assert_eq!(Ms(0.2).convert(&WindSpeedUnit::Beaufort), "0");
+    assert_eq!(Ms(3.654).convert(&WindSpeedUnit::Beaufort), "3");
+    assert_eq!(Ms(40.0).convert(&WindSpeedUnit::Beaufort), "12");
+    assert_eq!(Degree(350.0).cardinal(), "north");
+    assert_eq!(Degree(121.0).cardinal(), "south_east");
+    assert_eq!(Degree(0.0).arrow(), '↓');
+    assert_eq!(Degree(270.0).arrow(), '→');
+}
+
+#[test]
+fn fallback() {
+    let chain: Vec<WeatherCondition> =
+        std::iter::successors(Some(WeatherCondition::Sand), WeatherCondition::fallback).collect();
+
+    assert_eq!(
+        chain,
+        vec![
+            WeatherCondition::Sand,
+            WeatherCondition::Dust,
+            WeatherCondition::Haze,
+            WeatherCondition::Mist
+        ]
+    );
+    assert_eq!(WeatherCondition::ClearSky.fallback(), None);
 }
@@ -0,0 +1,233 @@
+use std::f64::consts::PI;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::model::astronomy::MoonPhase;
+use crate::model::location::Location;
+use crate::model::weather::Percentage;
+
+/// Altitudes of the sun's center in degrees
+const SUNRISE: f64 = -0.833;
+const CIVIL_TWILIGHT: f64 = -6.0;
+const GOLDEN_HOUR: f64 = 6.0;
+
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+/// Julian day of the new moon on 2000-01-06 18:14 UTC
+const NEW_MOON: f64 = 2_451_550.26;
+const UNIX_EPOCH: f64 = 2_440_587.5;
+
+/// Computes sun and moon events locally following the NOAA solar calculations
+pub struct AstronomyService {
+    lat: f64,
+    lon: f64,
+}
+
+impl AstronomyService {
+    pub fn new(location: &Location) -> Self {
+        Self {
+            lat: location.lat as f64,
+            lon: location.lon as f64,
+        }
+    }
+
+    /// The calendar day of the solar day at the location
+    pub fn date(&self, time: DateTime<Utc>) -> NaiveDate {
+        let solar_time = time + Duration::seconds((self.lon * 240.0) as i64);
+        solar_time.date_naive()
+    }
+
+    pub fn sunrise(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        self.sun_crossing(date, SUNRISE, true)
+    }
+
+    pub fn sunset(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        self.sun_crossing(date, SUNRISE, false)
+    }
+
+    pub fn civil_dawn(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        self.sun_crossing(date, CIVIL_TWILIGHT, true)
+    }
+
+    pub fn civil_dusk(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        self.sun_crossing(date, CIVIL_TWILIGHT, false)
+    }
+
+    /// Start of the evening golden hour
+    pub fn golden_hour(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        self.sun_crossing(date, GOLDEN_HOUR, false)
+    }
+
+    pub fn is_day(&self, time: DateTime<Utc>) -> bool {
+        let date = self.date(time);
+        match (self.sunrise(date), self.sunset(date)) {
+            (Some(sunrise), Some(sunset)) => sunrise <= time && time < sunset,
+            // polar days occur in the hemisphere the sun is currently above
+            _ => self.lat.signum() == Self::sun_position(date).0.signum(),
+        }
+    }
+
+    pub fn moon_phase(time: DateTime<Utc>) -> MoonPhase {
+        MoonPhase::from_cycle(Self::lunar_cycle(time))
+    }
+
+    pub fn moon_illumination(time: DateTime<Utc>) -> Percentage {
+        let illuminated = (1.0 - (2.0 * PI * Self::lunar_cycle(time)).cos()) / 2.0;
+        Percentage((illuminated * 100.0) as f32)
+    }
+
+    /// Fraction of the mean synodic month elapsed since the last new moon
+    fn lunar_cycle(time: DateTime<Utc>) -> f64 {
+        let age = (julian_day(time) - NEW_MOON) / SYNODIC_MONTH;
+        age.rem_euclid(1.0)
+    }
+
+    /// The time the sun's center passes the given altitude, or none at all on polar days/nights
+    fn sun_crossing(&self, date: NaiveDate, altitude: f64, rising: bool) -> Option<DateTime<Utc>> {
+        let (declination, equation_of_time) = Self::sun_position(date);
+        let cos_hour_angle =
+            (sin(altitude) - sin(self.lat) * sin(declination)) / (cos(self.lat) * cos(declination));
+        if !(-1.0..=1.0).contains(&cos_hour_angle) {
+            return None;
+        }
+        let hour_angle = cos_hour_angle.acos().to_degrees();
+
+        // in minutes since midnight UTC
+        let solar_noon = 720.0 - 4.0 * self.lon - equation_of_time;
+        let minutes = if rising {
+            solar_noon - 4.0 * hour_angle
+        } else {
+            solar_noon + 4.0 * hour_angle
+        };
+        let midnight = date.and_hms_opt(0, 0, 0)?.and_utc();
+        Some(midnight + Duration::seconds((minutes * 60.0).round() as i64))
+    }
+
+    /// The sun's declination and the equation of time in minutes at noon UTC
+    fn sun_position(date: NaiveDate) -> (f64, f64) {
+        let noon = date.and_hms_opt(12, 0, 0).unwrap().and_utc();
+        let t = (julian_day(noon) - 2_451_545.0) / 36525.0;
+
+        let mean_longitude = (280.46646 + t * (36000.76983 + t * 0.000_303_2)).rem_euclid(360.0);
+        let mean_anomaly = 357.52911 + t * (35999.05029 - 0.000_153_7 * t);
+        let eccentricity = 0.016_708_634 - t * (0.000_042_037 + 0.000_000_126_7 * t);
+        let center = sin(mean_anomaly) * (1.914_602 - t * (0.004_817 + 0.000_014 * t))
+            + sin(2.0 * mean_anomaly) * (0.019_993 - 0.000_101 * t)
+            + sin(3.0 * mean_anomaly) * 0.000_289;
+        let omega = 125.04 - 1934.136 * t;
+        let apparent_longitude = mean_longitude + center - 0.00569 - 0.00478 * sin(omega);
+
+        let mean_obliquity =
+            23.0 + (26.0 + (21.448 - t * (46.815 + t * (0.00059 - t * 0.001_813))) / 60.0) / 60.0;
+        let obliquity = mean_obliquity + 0.00256 * cos(omega);
+        let declination = (sin(obliquity) * sin(apparent_longitude))
+            .asin()
+            .to_degrees();
+
+        let y = (obliquity / 2.0).to_radians().tan().powi(2);
+        let equation_of_time = 4.0
+            * (y * sin(2.0 * mean_longitude) - 2.0 * eccentricity * sin(mean_anomaly)
+                + 4.0 * eccentricity * y * sin(mean_anomaly) * cos(2.0 * mean_longitude)
+                - 0.5 * y * y * sin(4.0 * mean_longitude)
+                - 1.25 * eccentricity * eccentricity * sin(2.0 * mean_anomaly))
+            .to_degrees();
+        (declination, equation_of_time)
+    }
+}
+
+fn julian_day(time: DateTime<Utc>) -> f64 {
+    time.timestamp() as f64 / 86400.0 + UNIX_EPOCH
+}
+
+fn sin(degrees: f64) -> f64 {
+    degrees.to_radians().sin()
+}
+
+fn cos(degrees: f64) -> f64 {
+    degrees.to_radians().cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+
+    use crate::model::astronomy::MoonPhase;
+    use crate::model::location::Location;
+    use crate::services::astronomy_service::AstronomyService;
+
+    fn service(lat: f32, lon: f32) -> AstronomyService {
+        AstronomyService::new(&Location {
+            city: None,
+            lat,
+            lon,
+        })
+    }
+
+    fn assert_close(actual: Option<DateTime<Utc>>, expected: DateTime<Utc>) {
+        let difference = (actual.unwrap() - expected).num_seconds().abs();
+        assert!(difference <= 120, "{:?} != {}", actual, expected);
+    }
+
+    #[test]
+    fn sun_london() {
+        // almanac values for the summer solstice
+        let london = service(51.5074, -0.1278);
+        let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+
+        let time = |h, m| Utc.with_ymd_and_hms(2024, 6, 21, h, m, 0).unwrap();
+        assert_close(london.sunrise(date), time(3, 43));
+        assert_close(london.sunset(date), time(20, 21));
+        assert_close(london.civil_dawn(date), time(2, 56));
+        assert_close(london.civil_dusk(date), time(21, 8));
+        assert!(london.golden_hour(date) < london.sunset(date));
+    }
+
+    #[test]
+    fn sun_sydney() {
+        let sydney = service(-33.8688, 151.2093);
+        let date = NaiveDate::from_ymd_opt(2024, 12, 21).unwrap();
+
+        assert_close(
+            sydney.sunrise(date),
+            Utc.with_ymd_and_hms(2024, 12, 20, 18, 41, 0).unwrap(),
+        );
+        assert_close(
+            sydney.sunset(date),
+            Utc.with_ymd_and_hms(2024, 12, 21, 9, 5, 0).unwrap(),
+        );
+    }
+
+    #[test]
+    fn polar_day() {
+        let tromso = service(69.6492, 18.9553);
+        let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+
+        assert_eq!(tromso.sunrise(date), None);
+        assert_eq!(tromso.sunset(date), None);
+        let midnight = Utc.with_ymd_and_hms(2024, 6, 21, 22, 0, 0).unwrap();
+        assert!(tromso.is_day(midnight));
+    }
+
+    #[test]
+    fn day_and_night() {
+        let london = service(51.5074, -0.1278);
+
+        assert!(london.is_day(Utc.with_ymd_and_hms(2024, 6, 21, 12, 0, 0).unwrap()));
+        assert!(!london.is_day(Utc.with_ymd_and_hms(2024, 6, 21, 23, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn moon() {
+        let new_moon = Utc.with_ymd_and_hms(2024, 1, 11, 11, 57, 0).unwrap();
+        let first_quarter = Utc.with_ymd_and_hms(2024, 1, 18, 3, 52, 0).unwrap();
+        let full_moon = Utc.with_ymd_and_hms(2024, 1, 25, 17, 54, 0).unwrap();
+
+        assert_eq!(AstronomyService::moon_phase(new_moon), MoonPhase::NewMoon);
+        assert!(AstronomyService::moon_illumination(new_moon).0 < 2.0);
+        assert_eq!(
+            AstronomyService::moon_phase(first_quarter),
+            MoonPhase::FirstQuarter
+        );
+        assert_eq!(AstronomyService::moon_phase(full_moon), MoonPhase::FullMoon);
+        assert!(AstronomyService::moon_illumination(full_moon).0 > 98.0);
+    }
+}
@@ -1,12 +1,12 @@
 use std::{env, process};
 
-use log::debug;
-use structopt::StructOpt;
+use log::{debug, warn};
 
 use crate::adapters::config::cli_args::CliArgs;
 use crate::adapters::config::file;
 use crate::model::config::Config;
-use std::path::Path;
+use crate::model::weather::WeatherProvider;
+use std::path::{Path, PathBuf};
 
 const WEDDER_WEATHER_API_KEY: &str = "WEDDER_WEATHER_API_KEY";
 
@@ -18,41 +18,99 @@ macro_rules! merge {
     };
 }
 
-pub fn config() -> Config {
-    let args = CliArgs::from_args();
+pub fn config(args: CliArgs) -> Config {
     args.apply();
-
-    let mut config = match &args.config_file {
+    let config = match &args.config_file {
         Some(path) => file::from_path(Path::new(path)),
         None => file::from_default_path(),
     };
+    match complete(config, args) {
+        Ok(config) => config,
+        Err(err) => {
+            println!("{}", err);
+            process::exit(1)
+        }
+    }
+}
+
+/// Rereads the config file of a running wedder, failing instead of exiting
+/// so the current config can be kept
+pub fn reload(args: &CliArgs) -> crate::Result<Config> {
+    let path = config_path(args).ok_or("Erroneous default config path")?;
+    let config = file::read(&path)?;
+    complete(config, args.clone())
+}
+
+/// The config file in use, which is empty if wedder runs without one
+pub fn config_path(args: &CliArgs) -> Option<PathBuf> {
+    match &args.config_file {
+        Some(path) => Some(PathBuf::from(path)),
+        None => file::default_config_path(),
+    }
+}
+
+fn complete(mut config: Config, args: CliArgs) -> crate::Result<Config> {
+    // subcommands don't query the weather
+    let queries_weather = args.command.is_none();
+
     debug!("Read {:#?}", config);
     merge_config_with_args(&mut config, args);
     debug!("Merged config with args into {:#?}", config);
 
-    if config.weather.api_key.is_empty() {
+    migrate_deprecated(&mut config);
+    if config.weather.openweathermap.api_key.is_empty() {
         if let Ok(key) = env::var(WEDDER_WEATHER_API_KEY) {
-            config.weather.api_key = key;
-        } else {
-            println!("No API key");
-            process::exit(1)
+            config.weather.openweathermap.api_key = key;
+        } else if queries_weather && config.weather.provider == WeatherProvider::OpenWeatherMap {
+            return Err("No API key".into());
+        }
+    }
+    validate(&config)?;
+    Ok(config)
+}
+
+/// Rejects options the providers can't use
+fn validate(config: &Config) -> crate::Result<()> {
+    let mqtt = &config.sensor.mqtt;
+    // MQTT 3.1.1 only allows a password together with a username
+    if !mqtt.password.is_empty() && mqtt.username.is_empty() {
+        return Err("sensor.mqtt.password requires sensor.mqtt.username".into());
+    }
+    Ok(())
+}
+
+/// Moves options from their deprecated place to their current one
+fn migrate_deprecated(config: &mut Config) {
+    if let Some(api_key) = config.weather.api_key.take() {
+        warn!("weather.api_key is deprecated, set weather.openweathermap.api_key instead");
+        if config.weather.openweathermap.api_key.is_empty() {
+            config.weather.openweathermap.api_key = api_key;
         }
     }
-    config
 }
 
 fn merge_config_with_args(config: &mut Config, args: CliArgs) {
+    if args.format.is_some() {
+        // a format passed explicitly is displayed instead of the configured formats
+        config.formats.clear();
+    }
     merge!(config.format, args.format);
     merge!(config.interval, args.interval);
     merge!(config.units.temperature, args.temperature_unit);
     merge!(config.units.wind_speed, args.wind_speed_unit);
     merge!(config.units.distance, args.distance_unit);
     merge!(config.units.precipitation, args.precipitation_unit);
+    merge!(config.units.aqi, args.aqi_unit);
+    merge!(config.time_zone, args.time_zone);
+    merge!(config.language, args.language);
     merge!(config.weather.provider, args.weather_provider);
-    merge!(config.weather.api_key, args.weather_api_key);
+    merge!(config.weather.openweathermap.api_key, args.weather_api_key);
     merge!(config.location.provider, args.location_provider);
+    merge!(config.location.interval, args.location_interval);
+    merge!(config.location.min_distance, args.min_distance);
     merge!(config.location.location.lat, args.lat);
     merge!(config.location.location.lon, args.lon);
+    merge!(config.history.length, args.history_length);
 }
 
 #[cfg(test)]
@@ -61,13 +119,14 @@ mod tests {
         adapters::config::cli_args::CliArgs,
         model::{
             config::{
-                Config, DistanceUnit, Format, Interval, PrecipitationUnit, TemperatureUnit,
-                WindSpeedUnit,
+                AqiUnit, Config, DistanceUnit, Format, HistoryLength, Interval, MinDistance,
+                PrecipitationUnit, TemperatureUnit, TimeZone, WindSpeedUnit,
             },
+            language::Language,
             location::LocationProvider,
             weather::WeatherProvider,
         },
-        services::config_service::merge_config_with_args,
+        services::config_service::{merge_config_with_args, migrate_deprecated, validate},
     };
 
     #[test]
@@ -77,10 +136,21 @@ mod tests {
         let default = Config::default();
 
         assert_eq!(file.format, default.format);
+        assert_eq!(file.formats, default.formats);
+        assert_eq!(file.stale_marker, default.stale_marker);
+        assert_eq!(file.stale_format, default.stale_format);
+        assert_eq!(file.max_age, default.max_age);
         assert_eq!(file.interval, default.interval);
+        assert_eq!(file.schedule, default.schedule);
         assert_eq!(file.units, default.units);
+        assert_eq!(file.time_zone, default.time_zone);
+        assert_eq!(file.language, default.language);
         assert_eq!(file.weather, default.weather);
         assert_eq!(file.location, default.location);
+        assert_eq!(file.sensor, default.sensor);
+        assert_eq!(file.history, default.history);
+        assert_eq!(file.recorder, default.recorder);
+        assert_eq!(file.tags, default.tags);
         assert_eq!(file.icons, default.icons);
     }
 
@@ -96,25 +166,67 @@ mod tests {
             wind_speed_unit: Some(WindSpeedUnit::Ms),
             distance_unit: Some(DistanceUnit::Mile),
             precipitation_unit: Some(PrecipitationUnit::Inch),
+            aqi_unit: Some(AqiUnit::Caqi),
+            time_zone: Some(TimeZone::Location),
+            language: Some(Language::De),
             weather_provider: Some(WeatherProvider::OpenWeatherMap),
             weather_api_key: Some("key".to_string()),
             location_provider: Some(LocationProvider::Manual),
+            location_interval: Some(Interval(3600)),
+            min_distance: Some(MinDistance(50)),
             lat: Some(1.0),
             lon: Some(1.0),
+            history_length: Some(HistoryLength(60)),
+            command: None,
+        };
+        let mut config = Config {
+            formats: vec![Format("alternative".to_string())],
+            ..Default::default()
         };
-        let mut config = Config::default();
         merge_config_with_args(&mut config, args.clone());
 
         assert_eq!(config.format, args.format.unwrap());
+        assert!(config.formats.is_empty());
         assert_eq!(config.interval, args.interval.unwrap());
         assert_eq!(config.units.temperature, TemperatureUnit::Kelvin);
         assert_eq!(config.units.wind_speed, WindSpeedUnit::Ms);
         assert_eq!(config.units.distance, DistanceUnit::Mile);
         assert_eq!(config.units.precipitation, PrecipitationUnit::Inch);
+        assert_eq!(config.units.aqi, AqiUnit::Caqi);
+        assert_eq!(config.time_zone, TimeZone::Location);
+        assert_eq!(config.language, Language::De);
         assert_eq!(config.weather.provider, args.weather_provider.unwrap());
-        assert_eq!(config.weather.api_key, args.weather_api_key.unwrap());
+        assert_eq!(
+            config.weather.openweathermap.api_key,
+            args.weather_api_key.unwrap()
+        );
         assert_eq!(config.location.provider, args.location_provider.unwrap());
+        assert_eq!(config.location.interval, args.location_interval.unwrap());
+        assert_eq!(config.location.min_distance, args.min_distance.unwrap());
         assert_eq!(config.location.location.lat, args.lat.unwrap());
         assert_eq!(config.location.location.lon, args.lon.unwrap());
+        assert_eq!(config.history.length, args.history_length.unwrap());
+    }
+
+    #[test]
+    fn deprecated_api_key() {
+        let mut config: Config = toml::from_str("[weather]\napi_key = 'key'").unwrap();
+        migrate_deprecated(&mut config);
+        assert_eq!(config.weather.api_key, None);
+        assert_eq!(config.weather.openweathermap.api_key, "key");
+
+        let cfg_str = "[weather]\napi_key = 'old'\n[weather.openweathermap]\napi_key = 'new'";
+        let mut config: Config = toml::from_str(cfg_str).unwrap();
+        migrate_deprecated(&mut config);
+        assert_eq!(config.weather.openweathermap.api_key, "new");
+    }
+
+    #[test]
+    fn mqtt_password_without_username() {
+        let mut config = Config::default();
+        config.sensor.mqtt.password = "secret".to_string();
+        assert!(validate(&config).is_err());
+        config.sensor.mqtt.username = "user".to_string();
+        assert!(validate(&config).is_ok());
     }
 }
@@ -1,114 +1,559 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::iter;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use crate::model::config::Config;
+use chrono::{DateTime, Duration, Local, TimeDelta, Utc};
+use log::warn;
+use rhai::{Dynamic, Engine, Scope};
+
+use crate::model::air_quality;
+use crate::model::air_quality::Concentration;
+use crate::model::config::{AqiUnit, Config, Format, TimeZone};
+use crate::model::language::Language;
 use crate::model::location::Location;
+use crate::model::pollen::Pollen;
+use crate::model::sensor::SensorReading;
 use crate::model::weather::Convert;
 use crate::model::weather::Weather;
+use crate::model::weather::WeatherCondition;
+use crate::services::astronomy_service::AstronomyService;
+use crate::services::history_service::HistoryService;
+
+/// Whether the fallback to the machine's time zone was logged, which would repeat on every redraw
+static WARNED_TIME_ZONE: AtomicBool = AtomicBool::new(false);
 
 macro_rules! tag {
     ($tags:expr, $option:expr) => {
         let tag = format!("<{}>", stringify!($option));
-        if let Some(value) = $option {
-            $tags.insert(tag, value.to_string());
-        } else {
-            $tags.insert(tag, "N/A".to_string());
-        }
+        $tags.insert(tag, $option.map(|value| value.to_string()));
     };
 }
 
 pub struct FormatService<'a> {
     config: &'a Config,
-    location: Location,
-    weather: Box<dyn Weather>,
+    format: &'a Format,
+    location: &'a Location,
+    weather: &'a dyn Weather,
+    pollen: Option<&'a dyn Pollen>,
+    history: &'a HistoryService,
+    indoor: Option<SensorReading>,
+    astronomy: AstronomyService,
 }
 
 impl<'a> FormatService<'a> {
-    pub fn new(config: &'a Config, location: Location, weather: Box<dyn Weather>) -> Self {
+    pub fn new(
+        config: &'a Config,
+        format: &'a Format,
+        location: &'a Location,
+        weather: &'a dyn Weather,
+        pollen: Option<&'a dyn Pollen>,
+        history: &'a HistoryService,
+        indoor: Option<SensorReading>,
+    ) -> Self {
+        let astronomy = AstronomyService::new(location);
         Self {
             config,
+            format,
             location,
             weather,
+            pollen,
+            history,
+            indoor,
+            astronomy,
+        }
+    }
+
+    /// Names of the tags referenced in any of the formats including the stale format and by the expressions of used
+    /// custom tags, used to skip fetching unused data
+    pub fn used_tags(config: &Config) -> HashSet<String> {
+        let mut tags: HashSet<String> = config
+            .formats()
+            .chain(&config.stale_format)
+            .flat_map(|format| format.0.split('<').skip(1))
+            .filter_map(|part| part.split_once('>'))
+            .map(|(tag, _)| tag)
+            .filter(|tag| {
+                !tag.is_empty() && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            })
+            .map(String::from)
+            .collect();
+        let referenced: Vec<String> = tags
+            .iter()
+            .filter_map(|tag| config.tags.get(tag))
+            .flat_map(|expression| {
+                expression
+                    .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .filter(|word| word.starts_with(|c: char| c.is_ascii_alphabetic()))
+                    .map(String::from)
+            })
+            .collect();
+        tags.extend(referenced);
+        tags
+    }
+
+    /// The format to display the last weather in and whether it is stale
+    ///
+    /// The weather is stale if the last poll failed or the last successful poll is older
+    /// than the max age, in which case the stale format is used instead if set.
+    pub fn select_format(
+        config: &Config,
+        index: usize,
+        failed: bool,
+        age: TimeDelta,
+    ) -> (&Format, bool) {
+        let too_old = age > TimeDelta::seconds(config.max_age.0.into());
+        match &config.stale_format {
+            Some(stale_format) if too_old => (stale_format, false),
+            _ => (config.format_at(index), failed || too_old),
         }
     }
 
     pub fn format(&self) -> String {
-        let mut formatted = self.config.format.0.to_string();
+        let mut formatted = self.format.0.to_string();
+        let not_available = self.config.language.translate("not_available");
         for (tag, value) in self.tags() {
-            formatted = formatted.replace(&tag, &value);
+            formatted = formatted.replace(&tag, value.as_deref().unwrap_or(&not_available));
         }
         formatted
     }
 
-    fn tags(&self) -> HashMap<String, String> {
+    fn tags(&self) -> HashMap<String, Option<String>> {
         let mut tags = HashMap::new();
         let temp_unit = &self.config.units.temperature;
+        let language = &self.config.language;
 
-        let city = &self.location.city;
+        let city = self.location.city.clone();
         let icon = self.icon();
+        let condition = self
+            .weather
+            .weather_condition()
+            .map(|condition| language.translate(&condition.to_string()));
+        let description = self.weather.description();
 
-        let temperature = self.weather.temp().map(|temp| temp.convert(temp_unit));
+        let temperature = self
+            .weather
+            .temp()
+            .map(|temp| temp.convert_localized(temp_unit, language));
         let temperature_feels_like = self
             .weather
             .temp_feels_like()
-            .map(|feels_like| feels_like.convert(temp_unit));
-        let temperature_max = self.weather.temp_max().map(|max| max.convert(temp_unit));
-        let temperature_min = self.weather.temp_min().map(|min| min.convert(temp_unit));
+            .map(|feels_like| feels_like.convert_localized(temp_unit, language));
+        let temperature_max = self
+            .weather
+            .temp_max()
+            .map(|max| max.convert_localized(temp_unit, language));
+        let temperature_min = self
+            .weather
+            .temp_min()
+            .map(|min| min.convert_localized(temp_unit, language));
         let dew_point = self
             .weather
             .dew_point()
-            .map(|dew_point| dew_point.convert(temp_unit));
-
-        let precipitation = self
+            .map(|dew_point| dew_point.convert_localized(temp_unit, language));
+        let indoor_temperature = self
+            .indoor
+            .and_then(|indoor| indoor.temperature)
+            .map(|temp| temp.convert_localized(temp_unit, language));
+        let indoor_humidity = self.indoor.and_then(|indoor| indoor.humidity);
+        let heat_index = self
+            .weather
+            .heat_index()
+            .map(|heat_index| heat_index.convert_localized(temp_unit, language));
+        let wind_chill = self
+            .weather
+            .wind_chill()
+            .map(|wind_chill| wind_chill.convert_localized(temp_unit, language));
+        let humidex = self
             .weather
-            .precipitation()
-            .map(|precipitation| precipitation.convert(&self.config.units.precipitation));
+            .humidex()
+            .map(|humidex| humidex.convert_localized(temp_unit, language));
+        let absolute_humidity = self
+            .weather
+            .absolute_humidity()
+            .map(|humidity| language.localize_number(&humidity.to_string()));
+
+        let precipitation = self.weather.precipitation().map(|precipitation| {
+            precipitation.convert_localized(&self.config.units.precipitation, language)
+        });
         let precipitation_chance = self.weather.precipitation_chance();
         let clouds = self.weather.clouds();
         let humidity = self.weather.humidity();
         let visibility = self
             .weather
             .visibility()
-            .map(|visibility| visibility.convert(&self.config.units.distance));
-        let wind_speed = self
+            .map(|visibility| visibility.convert_localized(&self.config.units.distance, language));
+        let wind_speed = self.weather.wind_speed().map(|wind_speed| {
+            wind_speed.convert_localized(&self.config.units.wind_speed, language)
+        });
+        let wind_gust = self
             .weather
-            .wind_speed()
-            .map(|wind_speed| wind_speed.convert(&self.config.units.wind_speed));
+            .wind_gust()
+            .map(|wind_gust| wind_gust.convert_localized(&self.config.units.wind_speed, language));
+        let wind_direction = self.weather.wind_direction();
+        let wind_cardinal =
+            wind_direction.map(|direction| language.translate(direction.cardinal()));
+        let wind_arrow = wind_direction.map(|direction| direction.arrow());
         let pressure = self.weather.pressure();
         let uv_index = self.weather.uvi();
-        let air_quality_index = self.weather.aqi();
+        let pollutants = self.weather.pollutants().unwrap_or_default();
+        let aqi_unit = &self.config.units.aqi;
+        let aqi = match aqi_unit {
+            AqiUnit::Owm => self.weather.aqi(),
+            AqiUnit::UsEpa => pollutants.us_epa(),
+            AqiUnit::Caqi => pollutants.caqi(),
+        };
+        let air_quality_index = aqi;
+        let air_quality = aqi.map(|aqi| language.translate(air_quality::category(aqi, aqi_unit)));
+        let concentration = |concentration: Option<Concentration>| {
+            concentration.map(|concentration| language.localize_number(&concentration.to_string()))
+        };
+        let pm2_5 = concentration(pollutants.pm2_5);
+        let pm10 = concentration(pollutants.pm10);
+        let o3 = concentration(pollutants.o3);
+        let no2 = concentration(pollutants.no2);
+        let so2 = concentration(pollutants.so2);
+        let co = concentration(pollutants.co);
+        let nh3 = concentration(pollutants.nh3);
+
+        let temperature_trend = self.history.temperature_trend().map(|trend| trend.arrow());
+        let pressure_trend = self.history.pressure_trend().map(|trend| trend.arrow());
+        let temperature_delta_1h = self
+            .history
+            .temperature_delta(3600, self.config.longest_interval().into())
+            .map(|delta| delta.convert_localized(temp_unit, language));
+
+        let pollen = self.pollen;
+        let alder_pollen = pollen.and_then(|pollen| pollen.alder());
+        let birch_pollen = pollen.and_then(|pollen| pollen.birch());
+        let grass_pollen = pollen.and_then(|pollen| pollen.grass());
+        let mugwort_pollen = pollen.and_then(|pollen| pollen.mugwort());
+        let olive_pollen = pollen.and_then(|pollen| pollen.olive());
+        let ragweed_pollen = pollen.and_then(|pollen| pollen.ragweed());
+        let pollen_level = pollen
+            .and_then(|pollen| pollen.level())
+            .map(|level| language.translate(&level.to_string()));
+
+        let now = Utc::now();
+        let today = self.astronomy.date(now);
+        let sunrise_time = self
+            .weather
+            .sunrise()
+            .or_else(|| self.astronomy.sunrise(today));
+        let sunset_time = self
+            .weather
+            .sunset()
+            .or_else(|| self.astronomy.sunset(today));
+        let sunrise = sunrise_time.map(|time| self.time(time));
+        let sunset = sunset_time.map(|time| self.time(time));
+        let day_length = sunrise_time
+            .zip(sunset_time)
+            .map(|(sunrise, sunset)| duration(sunset - sunrise));
+        let sunset_in = self
+            .next_sunset(now, sunset_time)
+            .map(|sunset| duration(sunset - now));
+        let civil_dawn = self.astronomy.civil_dawn(today).map(|time| self.time(time));
+        let civil_dusk = self.astronomy.civil_dusk(today).map(|time| self.time(time));
+        let golden_hour = self
+            .astronomy
+            .golden_hour(today)
+            .map(|time| self.time(time));
+
+        let observed_at = self.weather.observed_at();
+        let last_update = observed_at.map(|time| self.time(time));
+        let age = observed_at.map(|time| (now - time).num_minutes().max(0));
 
-        let sunrise = self.weather.sunrise().map(|time| time.format("%H:%M"));
-        let sunset = self.weather.sunset().map(|time| time.format("%H:%M"));
+        let phase = AstronomyService::moon_phase(now);
+        let moon_phase = Some(language.translate(&phase.to_string()));
+        let moon_icon = Some(self.icon_for(phase.to_string()));
+        let moon_illumination = Some(AstronomyService::moon_illumination(now));
 
         tag!(tags, city);
         tag!(tags, icon);
+        tag!(tags, condition);
+        tag!(tags, description);
         tag!(tags, temperature);
         tag!(tags, temperature_feels_like);
         tag!(tags, temperature_max);
         tag!(tags, temperature_min);
         tag!(tags, dew_point);
+        tag!(tags, indoor_temperature);
+        tag!(tags, indoor_humidity);
+        tag!(tags, heat_index);
+        tag!(tags, wind_chill);
+        tag!(tags, humidex);
+        tag!(tags, absolute_humidity);
         tag!(tags, precipitation);
         tag!(tags, precipitation_chance);
         tag!(tags, clouds);
         tag!(tags, humidity);
         tag!(tags, visibility);
         tag!(tags, wind_speed);
+        tag!(tags, wind_gust);
+        tag!(tags, wind_direction);
+        tag!(tags, wind_cardinal);
+        tag!(tags, wind_arrow);
         tag!(tags, pressure);
         tag!(tags, uv_index);
         tag!(tags, air_quality_index);
+        tag!(tags, air_quality);
+        tag!(tags, pm2_5);
+        tag!(tags, pm10);
+        tag!(tags, o3);
+        tag!(tags, no2);
+        tag!(tags, so2);
+        tag!(tags, co);
+        tag!(tags, nh3);
+        tag!(tags, temperature_trend);
+        tag!(tags, pressure_trend);
+        tag!(tags, temperature_delta_1h);
+        tag!(tags, alder_pollen);
+        tag!(tags, birch_pollen);
+        tag!(tags, grass_pollen);
+        tag!(tags, mugwort_pollen);
+        tag!(tags, olive_pollen);
+        tag!(tags, ragweed_pollen);
+        tag!(tags, pollen_level);
         tag!(tags, sunrise);
         tag!(tags, sunset);
+        tag!(tags, day_length);
+        tag!(tags, sunset_in);
+        tag!(tags, civil_dawn);
+        tag!(tags, civil_dusk);
+        tag!(tags, golden_hour);
+        tag!(tags, moon_phase);
+        tag!(tags, moon_icon);
+        tag!(tags, moon_illumination);
+        tag!(tags, last_update);
+        tag!(tags, age);
+
+        // built-in tags take precedence over custom tags of the same name
+        let custom_tags: Vec<_> = self
+            .config
+            .tags
+            .iter()
+            .filter(|(name, _)| !tags.contains_key(&format!("<{}>", name)))
+            .map(|(name, expression)| {
+                let value = evaluate(expression, &tags, language)
+                    .map_err(|err| warn!("Error evaluating tag <{}>: {}", name, err))
+                    .ok()
+                    .flatten();
+                (format!("<{}>", name), value)
+            })
+            .collect();
+        tags.extend(custom_tags);
         tags
     }
 
+    fn next_sunset(
+        &self,
+        now: DateTime<Utc>,
+        sunset: Option<DateTime<Utc>>,
+    ) -> Option<DateTime<Utc>> {
+        let today = self.astronomy.date(now);
+        let tomorrow = today.succ_opt()?;
+        [
+            sunset,
+            self.astronomy.sunset(today),
+            self.astronomy.sunset(tomorrow),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|sunset| *sunset > now)
+    }
+
+    fn time(&self, time: DateTime<Utc>) -> String {
+        let format = "%H:%M";
+        match (&self.config.time_zone, self.weather.utc_offset()) {
+            (TimeZone::Location, Some(offset)) => time.with_timezone(&offset).format(format),
+            (TimeZone::Location, None) => {
+                if !WARNED_TIME_ZONE.swap(true, Ordering::Relaxed) {
+                    warn!(
+                        "The weather provider doesn't report the time zone of the location, \
+                        showing times in the time zone of this machine"
+                    );
+                }
+                time.with_timezone(&Local).format(format)
+            }
+            (TimeZone::Machine, _) => time.with_timezone(&Local).format(format),
+        }
+        .to_string()
+    }
+
+    /// Looks up the icon of the condition, falling back to coarser conditions
+    /// and from night to day icons
     fn icon(&self) -> Option<String> {
-        let condition = self.weather.weather_condition()?.to_string();
-        let icon = self
-            .config
-            .icons
-            .get(&condition)
-            .unwrap_or(&condition)
-            .to_string();
+        let condition = self.weather.weather_condition()?;
+        let is_day = self.is_day();
+        let icon = iter::successors(Some(condition), WeatherCondition::fallback)
+            .find_map(|condition| {
+                let night_icon = if is_day {
+                    None
+                } else {
+                    self.config.icons.get(&format!("{}_night", condition))
+                };
+                night_icon.or_else(|| self.config.icons.get(&condition.to_string()))
+            })
+            .cloned()
+            .unwrap_or_else(|| condition.to_string());
         Some(icon)
     }
+
+    fn is_day(&self) -> bool {
+        self.weather
+            .is_day()
+            .unwrap_or_else(|| self.astronomy.is_day(Utc::now()))
+    }
+
+    fn icon_for(&self, name: String) -> String {
+        self.config.icons.get(&name).cloned().unwrap_or(name)
+    }
+}
+
+/// Evaluates a rhai expression with the values of the tags as variables, numbers as
+/// displayed in the configured units and missing values as `()`
+fn evaluate(
+    expression: &str,
+    tags: &HashMap<String, Option<String>>,
+    language: &Language,
+) -> crate::Result<Option<String>> {
+    let mut engine = Engine::new();
+    // guard against endless loops in the status bar
+    engine.set_max_operations(100_000);
+
+    let mut scope = Scope::new();
+    for (tag, value) in tags {
+        let name = tag.trim_start_matches('<').trim_end_matches('>');
+        let value = value
+            .as_deref()
+            .map_or(Dynamic::UNIT, |value| variable(value, language));
+        scope.push_dynamic(name, value);
+    }
+
+    let value: Dynamic = engine.eval_with_scope(&mut scope, expression)?;
+    let formatted = if value.is_unit() {
+        None
+    } else if value.is_float() {
+        Some(language.localize_number(&value.to_string()))
+    } else {
+        Some(value.to_string())
+    };
+    Ok(formatted)
+}
+
+fn variable(value: &str, language: &Language) -> Dynamic {
+    let number = language.delocalize_number(value);
+    // avoid turning names like "Nan" into numbers
+    if !number.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+') {
+        return value.into();
+    }
+    if let Ok(int) = number.parse::<i64>() {
+        int.into()
+    } else if let Ok(float) = number.parse::<f64>() {
+        float.into()
+    } else {
+        value.into()
+    }
+}
+
+fn duration(duration: Duration) -> String {
+    format!(
+        "{}:{:02}",
+        duration.num_hours(),
+        duration.num_minutes() % 60
+    )
+}
+
+#[test]
+fn used_tags() {
+    let config = Config {
+        format: Format("%{F#fff}<icon>%{F-} <temperature>°C <pm2_5> <invalid tag> <>".to_string()),
+        ..Default::default()
+    };
+    let tags = FormatService::used_tags(&config);
+    let expected = ["icon", "temperature", "pm2_5"].map(String::from);
+    assert_eq!(tags, HashSet::from(expected));
+
+    let config: Config = toml::from_str(
+        "formats = ['<icon>', '<city>: <humidity>%']\nstale_format = '<temperature> <age>m'",
+    )
+    .unwrap();
+    let tags = FormatService::used_tags(&config);
+    let expected = ["icon", "city", "humidity", "temperature", "age"].map(String::from);
+    assert_eq!(tags, HashSet::from(expected));
+}
+
+#[test]
+fn select_format() {
+    let mut config = Config {
+        formats: vec![Format("first".to_string()), Format("second".to_string())],
+        ..Default::default()
+    };
+    let fresh = TimeDelta::minutes(5);
+    let old = TimeDelta::hours(2);
+    let select = |config: &Config, failed, age| {
+        let (format, stale) = FormatService::select_format(config, 1, failed, age);
+        (format.0.clone(), stale)
+    };
+
+    assert_eq!(select(&config, false, fresh), ("second".to_string(), false));
+    assert_eq!(select(&config, true, fresh), ("second".to_string(), true));
+    // without a stale format, old weather is only marked
+    assert_eq!(select(&config, true, old), ("second".to_string(), true));
+
+    config.stale_format = Some(Format("<age> min ago".to_string()));
+    assert_eq!(select(&config, true, fresh), ("second".to_string(), true));
+    assert_eq!(
+        select(&config, true, old),
+        ("<age> min ago".to_string(), false)
+    );
+    assert_eq!(
+        select(&config, false, old),
+        ("<age> min ago".to_string(), false)
+    );
+}
+
+#[test]
+fn used_tags_of_custom_tags() {
+    let config: Config = toml::from_str(
+        r#"
+        format = "<bike> <temperature>"
+        [tags]
+        bike = "if wind_speed < 20 && precipitation_chance < 30 { '🚲' } else { '🚌' }"
+        umbrella = "pm2_5"
+        "#,
+    )
+    .unwrap();
+
+    let tags = FormatService::used_tags(&config);
+    assert!(tags.contains("bike"));
+    assert!(tags.contains("wind_speed"));
+    assert!(tags.contains("precipitation_chance"));
+    assert!(!tags.contains("pm2_5"));
+}
+
+#[test]
+fn evaluate_custom_tags() {
+    let tags = HashMap::from([
+        ("<city>".to_string(), Some("Nan".to_string())),
+        ("<wind_speed>".to_string(), Some("13,2".to_string())),
+        ("<temperature>".to_string(), Some("-3".to_string())),
+        ("<precipitation_chance>".to_string(), None),
+    ]);
+    let language = &Language::De;
+    let eval = |expression| evaluate(expression, &tags, language).unwrap();
+
+    let bike = "if wind_speed < 20 && temperature > 0 { '🚲' } else { '🚌' }";
+    assert_eq!(eval(bike), Some("🚌".to_string()));
+    assert_eq!(eval("wind_speed * 2"), Some("26,4".to_string()));
+    assert_eq!(eval("temperature + 1"), Some("-2".to_string()));
+    assert_eq!(eval("city"), Some("Nan".to_string()));
+    assert_eq!(eval("precipitation_chance"), None);
+    assert_eq!(
+        eval("if precipitation_chance == () { \"?\" } else { \"%\" }"),
+        Some("?".to_string())
+    );
+    // comparisons with missing values are false
+    assert_eq!(eval("precipitation_chance > 30"), Some("false".to_string()));
+    assert!(evaluate("wind_speed +", &tags, language).is_err());
+    assert!(evaluate("loop {}", &tags, language).is_err());
 }
@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+
+use chrono::Utc;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::adapters::cache;
+use crate::model::config::HistoryConfig;
+use crate::model::history::{Reading, Trend};
+use crate::model::weather::{KelvinDelta, Weather};
+
+const CACHE_NAME: &str = "history";
+
+/// Rolling history of recent readings to derive trends from
+pub struct HistoryService {
+    config: HistoryConfig,
+    readings: VecDeque<Reading>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct History {
+    readings: VecDeque<Reading>,
+}
+
+impl HistoryService {
+    pub fn new(config: &HistoryConfig) -> Self {
+        let readings = if config.persist {
+            cache::load(CACHE_NAME)
+                .map(|history: History| history.readings)
+                .unwrap_or_default()
+        } else {
+            VecDeque::new()
+        };
+        Self {
+            config: config.clone(),
+            readings,
+        }
+    }
+
+    pub fn record(&mut self, weather: &dyn Weather) {
+        self.push(Reading::new(Utc::now().timestamp(), weather));
+
+        if self.config.persist {
+            let history = History {
+                readings: self.readings.clone(),
+            };
+            if let Err(err) = cache::save(CACHE_NAME, &history) {
+                warn!("Couldn't cache history: {}", err);
+            }
+        }
+    }
+
+    fn push(&mut self, reading: Reading) {
+        let oldest = reading.time - self.config.length.0 as i64;
+        self.readings.push_back(reading);
+        while let Some(true) = self.readings.front().map(|reading| reading.time < oldest) {
+            self.readings.pop_front();
+        }
+        debug!("{} readings in history", self.readings.len());
+    }
+
+    pub fn temperature_trend(&self) -> Option<Trend> {
+        let change = self.change(|reading| reading.temperature)?;
+        Some(Trend::new(change, self.config.temperature_threshold.0))
+    }
+
+    pub fn pressure_trend(&self) -> Option<Trend> {
+        let change = self.change(|reading| reading.pressure)?;
+        Some(Trend::new(change, self.config.pressure_threshold.0))
+    }
+
+    /// Change compared to the reading closest to the given seconds ago, which has to be off
+    /// by at most the tolerance, e.g. the poll interval
+    pub fn temperature_delta(&self, seconds: i64, tolerance: i64) -> Option<KelvinDelta> {
+        let latest = self.readings.back()?;
+        let current = latest.temperature?;
+        let previous = self
+            .readings
+            .iter()
+            .filter_map(|reading| Some((latest.time - reading.time, reading.temperature?)))
+            .filter(|(age, _)| (age - seconds).abs() <= tolerance)
+            .min_by_key(|(age, _)| (age - seconds).abs())
+            .map(|(_, temperature)| temperature)?;
+        Some(KelvinDelta(current - previous))
+    }
+
+    /// Change of the latest value compared to the oldest one
+    fn change(&self, value: impl Fn(&Reading) -> Option<f32>) -> Option<f32> {
+        let latest = self.readings.back()?;
+        let current = value(latest)?;
+        let previous = self
+            .readings
+            .iter()
+            .take(self.readings.len() - 1)
+            .find_map(&value)?;
+        Some(current - previous)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::config::HistoryConfig;
+    use crate::model::history::{Reading, Trend};
+    use crate::services::history_service::HistoryService;
+
+    fn reading(minutes: i64, temperature: f32, pressure: f32) -> Reading {
+        Reading {
+            time: minutes * 60,
+            temperature: Some(temperature),
+            pressure: Some(pressure),
+        }
+    }
+
+    #[test]
+    fn trends() {
+        let mut history = HistoryService::new(&HistoryConfig::default());
+        history.push(reading(0, 280.0, 1015.0));
+        assert_eq!(history.temperature_trend(), None);
+
+        history.push(reading(30, 280.5, 1013.0));
+        history.push(reading(70, 282.0, 1012.5));
+        assert_eq!(history.temperature_trend(), Some(Trend::Rising));
+        assert_eq!(history.pressure_trend(), Some(Trend::Falling));
+        // compared to the reading at 0 minutes, 70 minutes ago
+        assert_eq!(history.temperature_delta(3600, 600).unwrap().0, 2.0);
+        assert_eq!(history.temperature_delta(3600, 300), None);
+        assert_eq!(history.temperature_delta(3 * 3600, 600), None);
+
+        history.push(reading(200, 282.5, 1012.5));
+        // the reading at 0 minutes fell out of the history
+        assert_eq!(history.readings.len(), 3);
+        assert_eq!(history.temperature_trend(), Some(Trend::Rising));
+        assert_eq!(history.pressure_trend(), Some(Trend::Steady));
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::{Duration, Instant};
+
+use log::{debug, error, warn};
+
+use crate::adapters::cache;
+use crate::model::config::{Interval, LocationConfig, MinDistance};
+use crate::model::location::{CurrentLocation, Location};
+
+const CACHE_NAME: &str = "location";
+
+pub struct LocationService {
+    current_location: Box<dyn CurrentLocation>,
+    interval: Interval,
+    min_distance: MinDistance,
+    last: Option<Location>,
+    last_poll: Option<Instant>,
+}
+
+impl LocationService {
+    pub fn new(config: &LocationConfig, current_location: Box<dyn CurrentLocation>) -> Self {
+        Self {
+            current_location,
+            interval: config.interval.clone(),
+            min_distance: config.min_distance.clone(),
+            last: None,
+            last_poll: None,
+        }
+    }
+
+    pub fn location(&mut self) -> crate::Result<Location> {
+        if let (false, Some(last)) = (self.is_due(), &self.last) {
+            debug!("Reusing location from last poll");
+            return Ok(last.clone());
+        }
+
+        debug!("Polling current location...");
+        match self.current_location.location() {
+            Ok(location) => {
+                debug!("{:#?}", location);
+                self.last_poll = Some(Instant::now());
+                Ok(self.update(location))
+            }
+            Err(err) => {
+                error!("Error polling location: {}", err);
+                let last = self.last.clone().or_else(|| cache::load(CACHE_NAME));
+                let last = last.ok_or(err)?;
+                warn!("Using last known location {:?}", last);
+                Ok(last)
+            }
+        }
+    }
+
+    fn is_due(&self) -> bool {
+        match self.last_poll {
+            None => true,
+            Some(_) if self.interval.0 < 0 => false,
+            Some(last_poll) => last_poll.elapsed() >= Duration::from_secs(self.interval.0 as u64),
+        }
+    }
+
+    fn update(&mut self, location: Location) -> Location {
+        if let Some(last) = &self.last {
+            let distance = last.distance(&location);
+            if distance < self.min_distance.0 as f32 {
+                debug!("Moved only {:.0}m, keeping last location", distance);
+                return last.clone();
+            }
+        }
+
+        if let Err(err) = cache::save(CACHE_NAME, &location) {
+            warn!("Couldn't cache location: {}", err);
+        }
+        self.last = Some(location.clone());
+        location
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    use crate::model::config::{Interval, LocationConfig, MinDistance};
+    use crate::model::location::{CurrentLocation, Location};
+    use crate::services::location_service::LocationService;
+
+    struct Mock(RefCell<VecDeque<Option<Location>>>);
+
+    impl CurrentLocation for Mock {
+        fn location(&self) -> crate::Result<Location> {
+            self.0
+                .borrow_mut()
+                .pop_front()
+                .flatten()
+                .ok_or_else(|| "mock error".into())
+        }
+    }
+
+    fn location(lat: f32, lon: f32) -> Location {
+        Location {
+            city: None,
+            lat,
+            lon,
+        }
+    }
+
+    fn service(locations: Vec<Option<Location>>, interval: i32) -> LocationService {
+        let config = LocationConfig {
+            interval: Interval(interval),
+            min_distance: MinDistance(1000),
+            ..Default::default()
+        };
+        let mock = Mock(RefCell::new(locations.into()));
+        LocationService::new(&config, Box::new(mock))
+    }
+
+    #[test]
+    fn last_known_location_on_error() {
+        let first = location(51.5, -0.1);
+        let mut service = service(vec![Some(first.clone()), None], 0);
+
+        assert_eq!(service.location().unwrap(), first);
+        assert_eq!(service.location().unwrap(), first);
+    }
+
+    #[test]
+    fn min_distance() {
+        let first = location(51.5, -0.1);
+        let nearby = location(51.501, -0.1);
+        let far = location(48.85, 2.35);
+        let mut service = service(
+            vec![Some(first.clone()), Some(nearby), Some(far.clone())],
+            0,
+        );
+
+        assert_eq!(service.location().unwrap(), first);
+        assert_eq!(service.location().unwrap(), first);
+        assert_eq!(service.location().unwrap(), far);
+    }
+
+    #[test]
+    fn interval() {
+        let first = location(51.5, -0.1);
+        let mut service = service(vec![Some(first.clone())], 3600);
+
+        assert_eq!(service.location().unwrap(), first);
+        // the mock would fail if it was polled again
+        assert_eq!(service.location().unwrap(), first);
+        assert!(service.last_poll.is_some());
+    }
+}
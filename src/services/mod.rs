@@ -1,2 +1,7 @@
+pub mod astronomy_service;
 pub mod config_service;
 pub mod format_service;
+pub mod history_service;
+pub mod location_service;
+pub mod record_service;
+pub mod schedule_service;
@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+use std::iter;
+
+use chrono::{Local, NaiveDate, TimeZone, Utc};
+use log::warn;
+
+use crate::adapters::recorder;
+use crate::model::config::Config;
+use crate::model::location::Location;
+use crate::model::pollen::{Pollen, POLLEN_TAGS};
+use crate::model::record::{Record, Recorder};
+use crate::model::weather::{Convert, Kelvin, Meter, Millimeter, Ms, Weather};
+
+/// Records the weather of each poll to summarize it later
+pub struct RecordService {
+    recorder: Box<dyn Recorder>,
+}
+
+impl RecordService {
+    pub fn new(config: &Config) -> crate::Result<Self> {
+        Ok(Self {
+            recorder: recorder::recorder(&config.recorder)?,
+        })
+    }
+
+    /// The tags of values the providers only query on demand, which are recorded regardless
+    /// of the format
+    pub fn used_tags(&self) -> HashSet<String> {
+        iter::once("air_quality_index")
+            .chain(POLLEN_TAGS)
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn record(
+        &mut self,
+        location: &Location,
+        weather: &dyn Weather,
+        pollen: Option<&dyn Pollen>,
+    ) {
+        let record = Record::new(Utc::now().timestamp(), location, weather, pollen);
+        if let Err(err) = self.recorder.record(&record) {
+            warn!("Couldn't record weather: {}", err);
+        }
+    }
+
+    /// Minimum, maximum and average of the recorded values from the first to the last local day
+    pub fn summary(
+        &self,
+        config: &Config,
+        first: NaiveDate,
+        last: NaiveDate,
+    ) -> crate::Result<String> {
+        let start = local_midnight(first)?;
+        let end = local_midnight(last.succ_opt().ok_or("Invalid date")?)?;
+        let records = self.recorder.records(start, end)?;
+
+        let mut summary = format!("{} - {}: {} records\n", first, last, records.len());
+        let names = Record::default().values().map(|(name, _)| name);
+        let record_values: Vec<_> = records.iter().map(Record::values).collect();
+        for (i, name) in names.iter().enumerate() {
+            let values: Vec<f32> = record_values
+                .iter()
+                .filter_map(|values| values[i].1)
+                .collect();
+            if values.is_empty() {
+                continue;
+            }
+            let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+            let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            let avg = values.iter().sum::<f32>() / values.len() as f32;
+            summary.push_str(&format!(
+                "{:<24}min {:>8}  max {:>8}  avg {:>8}\n",
+                name,
+                convert(config, name, min),
+                convert(config, name, max),
+                convert(config, name, avg)
+            ));
+        }
+        Ok(summary)
+    }
+}
+
+fn local_midnight(date: NaiveDate) -> crate::Result<i64> {
+    let midnight = date.and_hms_opt(0, 0, 0).ok_or("Invalid date")?;
+    let local = Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .ok_or("Invalid local time")?;
+    Ok(local.timestamp())
+}
+
+/// Converts an SI value into the configured unit
+fn convert(config: &Config, name: &str, value: f32) -> String {
+    let units = &config.units;
+    match name {
+        "temperature"
+        | "temperature_feels_like"
+        | "temperature_max"
+        | "temperature_min"
+        | "dew_point"
+        | "heat_index"
+        | "wind_chill"
+        | "humidex" => Kelvin(value).convert(&units.temperature),
+        "precipitation" => Millimeter(value).convert(&units.precipitation),
+        "visibility" => Meter(value).convert(&units.distance),
+        "wind_speed" | "wind_gust" => Ms(value).convert(&units.wind_speed),
+        _ => format!("{:.1}", value),
+    }
+}
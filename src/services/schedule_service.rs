@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::model::config::{Interval, ScheduleConfig};
+use crate::model::weather::Freshness;
+
+/// Providers take a moment to publish the data of their update time
+const ALIGN_DELAY: i64 = 30;
+/// In seconds, how soon a failed poll is retried
+const RETRY_INTERVAL: i32 = 15;
+
+/// Decides when to poll next, either after the fixed interval or adapted to the circumstances
+pub struct ScheduleService {
+    config: ScheduleConfig,
+}
+
+/// What the last poll and the machine tell about when to poll next
+#[derive(Debug, Default)]
+pub struct Circumstances {
+    /// The last poll failed, e.g. because the network is down
+    pub failed: bool,
+    /// It rains, snows or storms
+    pub eventful: bool,
+    pub night: bool,
+    pub on_battery: bool,
+    /// How often the weather provider publishes new data
+    pub update_period: Option<Duration>,
+    pub freshness: Freshness,
+}
+
+impl ScheduleService {
+    pub fn new(config: &ScheduleConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    pub fn next_poll(
+        &self,
+        interval: &Interval,
+        circumstances: &Circumstances,
+        now: DateTime<Utc>,
+    ) -> DateTime<Utc> {
+        let mut next_poll = now + TimeDelta::seconds(self.interval(interval, circumstances).into());
+        // a failed poll is retried soon, without waiting for the provider's next update
+        if self.config.adaptive && !circumstances.failed {
+            if let Some(period) = circumstances.update_period.filter(|_| self.config.align) {
+                next_poll = align(next_poll, period);
+            }
+            // polling earlier would only get the cached response again
+            if let Some(expires) = circumstances.freshness.expires {
+                next_poll = next_poll.max(expires);
+            }
+        }
+        // rate limits are respected regardless of the schedule
+        if let Some(retry_after) = circumstances.freshness.retry_after {
+            next_poll = next_poll.max(retry_after);
+        }
+        next_poll
+    }
+
+    fn interval(&self, interval: &Interval, circumstances: &Circumstances) -> i32 {
+        let mut interval = interval.0;
+        if circumstances.failed {
+            return interval.min(RETRY_INTERVAL);
+        }
+        if !self.config.adaptive {
+            return interval;
+        }
+        if circumstances.eventful {
+            interval = interval.min(self.config.precipitation_interval.0);
+        } else if circumstances.night {
+            interval = interval.max(self.config.night_interval.0);
+        }
+        if circumstances.on_battery {
+            interval = interval.max(self.config.battery_interval.0);
+        }
+        interval
+    }
+}
+
+/// The first update time of the provider at or after the time
+fn align(time: DateTime<Utc>, period: Duration) -> DateTime<Utc> {
+    let period = period.as_secs() as i64;
+    if period == 0 {
+        return time;
+    }
+    let updates = (time.timestamp() - ALIGN_DELAY + period - 1).div_euclid(period);
+    DateTime::from_timestamp(updates * period + ALIGN_DELAY, 0).unwrap_or(time)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::DateTime;
+
+    use crate::model::config::{Interval, ScheduleConfig};
+    use crate::model::weather::Freshness;
+    use crate::services::schedule_service::{Circumstances, ScheduleService};
+
+    fn delay(service: &ScheduleService, circumstances: &Circumstances) -> i64 {
+        // a full hour
+        let now = DateTime::from_timestamp(1_699_999_200, 0).unwrap();
+        let next_poll = service.next_poll(&Interval(300), circumstances, now);
+        (next_poll - now).num_seconds()
+    }
+
+    #[test]
+    fn fixed() {
+        let service = ScheduleService::new(&ScheduleConfig::default());
+        let circumstances = Circumstances {
+            eventful: true,
+            on_battery: true,
+            update_period: Some(Duration::from_secs(600)),
+            ..Default::default()
+        };
+        assert_eq!(delay(&service, &circumstances), 300);
+
+        let circumstances = Circumstances {
+            freshness: Freshness {
+                expires: None,
+                retry_after: DateTime::from_timestamp(1_699_999_200 + 3600, 0),
+            },
+            ..Default::default()
+        };
+        assert_eq!(delay(&service, &circumstances), 3600);
+
+        let failed = Circumstances {
+            failed: true,
+            ..Default::default()
+        };
+        assert_eq!(delay(&service, &failed), 15);
+    }
+
+    #[test]
+    fn adaptive() {
+        let service = ScheduleService::new(&ScheduleConfig {
+            adaptive: true,
+            ..Default::default()
+        });
+        assert_eq!(delay(&service, &Circumstances::default()), 300);
+
+        let eventful = Circumstances {
+            eventful: true,
+            night: true,
+            ..Default::default()
+        };
+        assert_eq!(delay(&service, &eventful), 120);
+        let night = Circumstances {
+            night: true,
+            ..Default::default()
+        };
+        assert_eq!(delay(&service, &night), 900);
+        let on_battery = Circumstances {
+            eventful: true,
+            on_battery: true,
+            ..Default::default()
+        };
+        assert_eq!(delay(&service, &on_battery), 900);
+
+        // the provider's update at 10 minutes past the hour is published at 10:30
+        let aligned = Circumstances {
+            update_period: Some(Duration::from_secs(600)),
+            ..Default::default()
+        };
+        assert_eq!(delay(&service, &aligned), 630);
+        let failed = Circumstances {
+            failed: true,
+            update_period: Some(Duration::from_secs(600)),
+            ..Default::default()
+        };
+        assert_eq!(delay(&service, &failed), 15);
+        let cached = Circumstances {
+            update_period: Some(Duration::from_secs(600)),
+            freshness: Freshness {
+                expires: DateTime::from_timestamp(1_699_999_200 + 3600, 0),
+                retry_after: None,
+            },
+            ..Default::default()
+        };
+        assert_eq!(delay(&service, &cached), 3600);
+    }
+}
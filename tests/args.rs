@@ -93,3 +93,15 @@ fn no_api_key_but_env() {
         .assert()
         .success();
 }
+
+#[test]
+fn history() {
+    // the recorder is disabled by default, so there is nothing to summarize
+    create_cmd()
+        .arg("history")
+        .arg("--from")
+        .arg("1970-01-01")
+        .assert()
+        .success()
+        .stdout("1970-01-01 - 1970-01-01: 0 records\n");
+}
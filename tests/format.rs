@@ -1,3 +1,5 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use crate::common::create_cmd;
 
 mod common;
@@ -18,6 +20,30 @@ fn city() {
         .stdout("Montreal\n");
 }
 
+#[test]
+fn condition() {
+    let format = "<condition>, <description>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .assert()
+        .success()
+        .stdout("Mist, mist\n");
+}
+
+#[test]
+fn language() {
+    let format = "<condition>, <wind_speed>, <uv_index>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .arg("-L")
+        .arg("de")
+        .assert()
+        .success()
+        .stdout("Dunst, 13,2, k. A.\n");
+}
+
 #[test]
 fn temperature_celsius() {
     let format = "<temperature>, <temperature_feels_like>, \
@@ -27,7 +53,7 @@ fn temperature_celsius() {
         .arg(format)
         .assert()
         .success()
-        .stdout("2, -3, 6, 0, N/A\n");
+        .stdout("2, -3, 6, 0, 1\n");
 }
 
 #[test]
@@ -41,7 +67,7 @@ fn temperature_fahrenheit() {
         .arg("Fahrenheit")
         .assert()
         .success()
-        .stdout("35, 27, 43, 32, N/A\n");
+        .stdout("35, 27, 43, 32, 34\n");
 }
 
 #[test]
@@ -55,7 +81,18 @@ fn temperature_kelvin() {
         .arg("Kelvin")
         .assert()
         .success()
-        .stdout("275, 270, 279, 273, N/A\n");
+        .stdout("275, 270, 279, 273, 274\n");
+}
+
+#[test]
+fn comfort() {
+    let format = "<heat_index>, <wind_chill>, <humidex>, <absolute_humidity>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .assert()
+        .success()
+        .stdout("N/A, -2, N/A, 5.2\n");
 }
 
 #[test]
@@ -95,6 +132,30 @@ fn wind_speed_mph() {
         .stdout("8.2\n");
 }
 
+#[test]
+fn wind_speed_beaufort() {
+    let format = "<wind_speed>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .arg("-s")
+        .arg("Beaufort")
+        .assert()
+        .success()
+        .stdout("3\n");
+}
+
+#[test]
+fn wind() {
+    let format = "<wind_gust>, <wind_direction>, <wind_cardinal>, <wind_arrow>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .assert()
+        .success()
+        .stdout("12.5, 121, SE, ↖\n");
+}
+
 #[test]
 fn distance_meter() {
     let format = "<visibility>";
@@ -167,3 +228,119 @@ fn other() {
         .success()
         .stdout("1018, 97, 91, N/A, N/A, 1\n");
 }
+
+#[test]
+fn air_quality() {
+    let format =
+        "<air_quality_index> <air_quality>, <pm2_5>, <pm10>, <o3>, <no2>, <so2>, <co>, <nh3>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .assert()
+        .success()
+        .stdout("1 Good, 0.5, 0.5, 68.7, 0.8, 0.6, 201.9, 0.1\n");
+}
+
+#[test]
+fn air_quality_us_epa() {
+    let format = "<air_quality_index> <air_quality>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .arg("-A")
+        .arg("UsEpa")
+        .assert()
+        .success()
+        .stdout("32 Good\n");
+}
+
+#[test]
+fn air_quality_caqi() {
+    let format = "<air_quality_index> <air_quality>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .arg("-A")
+        .arg("Caqi")
+        .assert()
+        .success()
+        .stdout("29 Low\n");
+}
+
+#[test]
+fn pollen() {
+    let format = "<alder_pollen>, <birch_pollen>, <grass_pollen>, <mugwort_pollen>, \
+    <olive_pollen>, <ragweed_pollen>, <pollen_level>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .assert()
+        .success()
+        .stdout("0, 42, 3, 0, N/A, 0, Moderate\n");
+}
+
+#[test]
+fn history() {
+    // a single poll has no previous readings to compare with
+    let format = "<temperature_trend>, <pressure_trend>, <temperature_delta_1h>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .assert()
+        .success()
+        .stdout("N/A, N/A, N/A\n");
+}
+
+#[test]
+fn time_zone_location() {
+    let format = "<sunrise>, <sunset>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .arg("-z")
+        .arg("Location")
+        .assert()
+        .success()
+        .stdout("15:13, 01:10\n");
+}
+
+#[test]
+fn last_update() {
+    let format = "<last_update>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .arg("-z")
+        .arg("Location")
+        .assert()
+        .success()
+        .stdout("13:59\n");
+}
+
+#[test]
+fn age() {
+    // the mocked weather was observed at 1726660758
+    let observed_at = 1726660758;
+    let output = create_cmd().arg("-f").arg("<age>").output().unwrap();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let age: u64 = String::from_utf8(output.stdout)
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap();
+    assert!(age.abs_diff((now - observed_at) / 60) <= 1);
+}
+
+#[test]
+fn day_length() {
+    let format = "<day_length>";
+    create_cmd()
+        .arg("-f")
+        .arg(format)
+        .assert()
+        .success()
+        .stdout("9:56\n");
+}